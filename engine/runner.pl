@@ -0,0 +1,44 @@
+:- use_module(library(format)).
+
+% Runs a goal against a script written by a user. The goal is read from goal.pl rather than
+% passed on the command line, as it may hold a whole game's state. The script is read one clause
+% at a time and each clause is asserted, rather than consulting it, so that a directive in the
+% script is never run. If anything goes wrong, only the kind of error is written to standard
+% error, so the server can report it without passing on anything the script printed.
+
+load_script(Path) :-
+    open(Path, read, Stream),
+    load_clauses(Stream),
+    close(Stream).
+
+load_clauses(Stream) :-
+    read_term(Stream, Term, []),
+    (   Term == end_of_file -> true
+    ;   load_clause(Term),
+        load_clauses(Stream)
+    ).
+
+load_clause((:- _)) :- !, throw(script_error(directive)).
+load_clause((?- _)) :- !, throw(script_error(directive)).
+load_clause((_ --> _)) :- !, throw(script_error(grammar_rule)).
+load_clause(Clause) :- assertz(Clause).
+
+read_goal(Path, Goal) :-
+    open(Path, read, Stream),
+    read_term(Stream, Goal, []),
+    close(Stream).
+
+run :-
+    catch(run_script, Error, (report(Error), halt(1))),
+    halt.
+
+run_script :-
+    read_goal('goal.pl', Goal),
+    load_script('script.pl'),
+    (   call(Goal) -> true
+    ;   throw(script_error(failed))
+    ).
+
+report(script_error(Kind)) :- !, format(user_error, "~a~n", [Kind]).
+report(error(Error, _)) :- !, functor(Error, Kind, _), format(user_error, "~a~n", [Kind]).
+report(_) :- format(user_error, "uncaught~n", []).
@@ -0,0 +1,44 @@
+use super::Storage;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use tokio::fs;
+
+/// Stores assets as files in a directory on this machine.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> anyhow::Result<PathBuf> {
+        anyhow::ensure!(
+            key.len() > 2 && key.chars().all(|ch| ch.is_ascii_hexdigit()),
+            "Invalid asset key: {}",
+            key,
+        );
+        Ok(self.root.join(&key[..2]).join(key))
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, _content_type: &str, content: Vec<u8>) -> anyhow::Result<()> {
+        let path = self.path(key)?;
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).await?;
+        }
+        fs::write(path, content).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)?).await {
+            Ok(content) => Ok(Some(content)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
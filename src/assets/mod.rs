@@ -0,0 +1,188 @@
+use crate::schema::Database;
+use anyhow::anyhow;
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use sha2::{Digest, Sha256};
+use std::env;
+use uuid::Uuid;
+
+mod local;
+#[cfg(feature = "s3")]
+mod s3;
+
+pub use local::LocalStorage;
+#[cfg(feature = "s3")]
+pub use s3::S3Storage;
+
+/// The largest single asset that may be uploaded, in bytes.
+pub const MAX_ASSET_SIZE: u64 = 8 * 1024 * 1024;
+
+/// The largest upload request that will be read, in bytes.
+pub const MAX_UPLOAD_SIZE: u64 = 4 * MAX_ASSET_SIZE;
+
+/// The most assets a single account may upload.
+pub const MAX_ACCOUNT_ASSETS: i64 = 500;
+
+/// The most bytes of assets a single account may upload, in total.
+pub const MAX_ACCOUNT_ASSET_SIZE: i64 = 256 * 1024 * 1024;
+
+/// The kinds of content that may be stored as assets. Only raster images are accepted, so that
+/// no asset can be rendered as a document.
+pub const ALLOWED_CONTENT_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// Somewhere to keep the content of assets. Content is stored under the hash of its contents,
+/// so a key always refers to the same content.
+#[async_trait::async_trait]
+pub trait Storage: Send + Sync {
+    /// Stores content under a key.
+    async fn put(&self, key: &str, content_type: &str, content: Vec<u8>) -> anyhow::Result<()>;
+
+    /// Retrieves the content stored under a key, if there is any.
+    async fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>>;
+}
+
+/// Chooses where to store assets based on the `ASSET_STORAGE` environment variable.
+pub fn from_env() -> anyhow::Result<Box<dyn Storage>> {
+    let storage = env::var("ASSET_STORAGE").unwrap_or_else(|_| String::from("local"));
+    match storage.as_str() {
+        "local" => {
+            let directory = env::var("ASSET_DIRECTORY").unwrap_or_else(|_| String::from("assets"));
+            Ok(Box::new(LocalStorage::new(directory)))
+        }
+        #[cfg(feature = "s3")]
+        "s3" => Ok(Box::new(S3Storage::from_env()?)),
+        _ => Err(anyhow!("Unsupported asset storage: {}", storage)),
+    }
+}
+
+/// Whether an asset with this content type may be served as is. Anything else is only ever
+/// served as an attachment.
+pub fn is_allowed_content_type(content_type: &str) -> bool {
+    ALLOWED_CONTENT_TYPES.contains(&content_type)
+}
+
+/// Works out the content type of some content from the bytes it begins with, rather than
+/// trusting the type it was uploaded with.
+pub fn content_type_of(content: &[u8]) -> Option<&'static str> {
+    if content.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if content.starts_with(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if content.starts_with(b"GIF87a") || content.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if content.len() >= 12 && &content[0..4] == b"RIFF" && &content[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Ensures that an account has room left to upload another asset of the given size.
+fn assert_within_account_limits(
+    account_id: Uuid,
+    size: i64,
+    conn: &DbConnection,
+) -> anyhow::Result<()> {
+    let (count, total): (i64, Option<i64>) = assets::table
+        .filter(assets::created_by.eq(account_id))
+        .select((count_star(), sum(assets::size)))
+        .get_result(conn)?;
+    anyhow::ensure!(
+        count < MAX_ACCOUNT_ASSETS,
+        "Accounts may upload at most {} assets",
+        MAX_ACCOUNT_ASSETS,
+    );
+    anyhow::ensure!(
+        total.unwrap_or(0) + size <= MAX_ACCOUNT_ASSET_SIZE,
+        "Accounts may upload at most {} bytes of assets",
+        MAX_ACCOUNT_ASSET_SIZE,
+    );
+    Ok(())
+}
+
+/// The key under which some content is stored.
+pub fn hash(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Stores an uploaded asset. The content type is taken from the content itself, and only
+/// raster images are accepted. If the same content has been uploaded before, the existing
+/// asset is returned instead of storing it again.
+pub async fn upload(
+    storage: &dyn Storage,
+    database: &Database,
+    account_id: Uuid,
+    content: Vec<u8>,
+) -> anyhow::Result<Asset> {
+    anyhow::ensure!(
+        content.len() as u64 <= MAX_ASSET_SIZE,
+        "Assets may be at most {} bytes",
+        MAX_ASSET_SIZE,
+    );
+    let content_type = content_type_of(&content)
+        .ok_or_else(|| anyhow!("Assets must be PNG, JPEG, GIF or WebP images"))?;
+    let hash = hash(&content);
+    let existing = tokio::task::block_in_place(|| -> anyhow::Result<Option<Asset>> {
+        Ok(assets::table
+            .find(&hash)
+            .get_result(&database.connection()?)
+            .optional()?)
+    })?;
+    if let Some(asset) = existing {
+        return Ok(asset);
+    }
+
+    let size = content.len() as i32;
+    tokio::task::block_in_place(|| {
+        assert_within_account_limits(account_id, size as i64, &database.connection()?)
+    })?;
+    storage.put(&hash, content_type, content).await?;
+    tokio::task::block_in_place(|| {
+        database.transaction(|conn| {
+            // Lock the account so that concurrent uploads cannot both squeeze under the limits.
+            accounts::table
+                .find(account_id)
+                .select(accounts::id)
+                .for_update()
+                .get_result::<Uuid>(conn)?;
+            if let Some(asset) = assets::table.find(&hash).get_result(conn).optional()? {
+                return Ok(asset);
+            }
+            assert_within_account_limits(account_id, size as i64, conn)?;
+            insert_into(assets::table)
+                .values((
+                    assets::hash.eq(&hash),
+                    assets::content_type.eq(content_type),
+                    assets::size.eq(size),
+                    assets::created_by.eq(account_id),
+                ))
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+            Ok(assets::table.find(&hash).get_result(conn)?)
+        })
+    })
+}
+
+/// Retrieves an asset and its content.
+pub async fn download(
+    storage: &dyn Storage,
+    database: &Database,
+    hash: &str,
+) -> anyhow::Result<Option<(Asset, Vec<u8>)>> {
+    let asset = tokio::task::block_in_place(|| -> anyhow::Result<Option<Asset>> {
+        Ok(assets::table
+            .find(hash)
+            .get_result(&database.connection()?)
+            .optional()?)
+    })?;
+    let asset = match asset {
+        Some(asset) => asset,
+        None => return Ok(None),
+    };
+    let content = storage
+        .get(&asset.hash)
+        .await?
+        .ok_or_else(|| anyhow!("The content of asset {} is missing from storage", hash))?;
+    Ok(Some((asset, content)))
+}
@@ -0,0 +1,64 @@
+use super::Storage;
+use rusoto_core::{Region, RusotoError};
+use rusoto_s3::{GetObjectError, GetObjectRequest, PutObjectRequest, S3Client, S3};
+use std::env;
+use tokio::io::AsyncReadExt;
+
+/// Stores assets in a bucket of any S3-compatible object storage.
+pub struct S3Storage {
+    client: S3Client,
+    bucket: String,
+}
+
+impl S3Storage {
+    /// Configures the bucket from `ASSET_S3_BUCKET`, `ASSET_S3_REGION` and, for services other
+    /// than AWS, `ASSET_S3_ENDPOINT`. Credentials are read from the usual AWS environment
+    /// variables.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let bucket = env::var("ASSET_S3_BUCKET")?;
+        let name = env::var("ASSET_S3_REGION").unwrap_or_else(|_| String::from("us-east-1"));
+        let region = match env::var("ASSET_S3_ENDPOINT") {
+            Ok(endpoint) => Region::Custom { name, endpoint },
+            Err(..) => name.parse()?,
+        };
+        Ok(Self {
+            client: S3Client::new(region),
+            bucket,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, content_type: &str, content: Vec<u8>) -> anyhow::Result<()> {
+        self.client
+            .put_object(PutObjectRequest {
+                bucket: self.bucket.clone(),
+                key: key.to_owned(),
+                content_type: Some(content_type.to_owned()),
+                body: Some(content.into()),
+                ..PutObjectRequest::default()
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let request = GetObjectRequest {
+            bucket: self.bucket.clone(),
+            key: key.to_owned(),
+            ..GetObjectRequest::default()
+        };
+        match self.client.get_object(request).await {
+            Ok(output) => {
+                let mut content = vec![];
+                if let Some(body) = output.body {
+                    body.into_async_read().read_to_end(&mut content).await?;
+                }
+                Ok(Some(content))
+            }
+            Err(RusotoError::Service(GetObjectError::NoSuchKey(..))) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use lib::assets::{self, Storage, MAX_UPLOAD_SIZE};
+use lib::jwt::AuthenticatedAccount;
+use lib::schema::Database;
+use multer::Multipart;
+use rocket::http::{ContentType, Header, Status};
+use rocket::response::{self, content, status, Responder};
+use rocket::{Data, Request, State};
+use tokio::io::AsyncReadExt;
+use tokio_util::codec::{BytesCodec, FramedRead};
+
+fn bad_request(error: impl ToString) -> status::Custom<String> {
+    status::Custom(Status::BadRequest, error.to_string())
+}
+
+/// The content of an asset. Browsers are told not to guess at its type, and anything that is not
+/// an allowed image is sent as an attachment so that it is never displayed inline.
+pub struct AssetContent {
+    content_type: String,
+    content: Vec<u8>,
+}
+
+impl<'r> Responder<'r, 'static> for AssetContent {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let allowed = assets::is_allowed_content_type(&self.content_type);
+        let content_type = if allowed {
+            ContentType::parse_flexible(&self.content_type).unwrap_or(ContentType::Binary)
+        } else {
+            ContentType::Binary
+        };
+        let mut response = content::Content(content_type, self.content).respond_to(request)?;
+        response.set_header(Header::new("X-Content-Type-Options", "nosniff"));
+        if !allowed {
+            response.set_header(Header::new("Content-Disposition", "attachment"));
+        }
+        Ok(response)
+    }
+}
+
+/// Upload one or more assets as `multipart/form-data`. Every field of the form is stored as an
+/// asset, and the hash, content type and size of each is returned in the order they were sent.
+/// Only PNG, JPEG, GIF and WebP images may be uploaded; the type is worked out from the content
+/// rather than taken from the form.
+#[rocket::post("/assets", data = "<data>")]
+pub async fn upload<'a>(
+    database: State<'a, Database>,
+    storage: State<'a, Box<dyn Storage>>,
+    account_id: AuthenticatedAccount,
+    content_type: &ContentType,
+    data: Data,
+) -> Result<content::Json<String>, status::Custom<String>> {
+    let boundary = content_type
+        .params()
+        .find(|&(name, _)| name == "boundary")
+        .map(|(_, boundary)| boundary)
+        .ok_or_else(|| bad_request("Assets must be uploaded as multipart/form-data"))?;
+    let stream = FramedRead::new(data.open().take(MAX_UPLOAD_SIZE), BytesCodec::new());
+    let mut multipart = Multipart::new(stream, boundary);
+
+    let mut uploaded = vec![];
+    while let Some(field) = multipart.next_field().await.map_err(bad_request)? {
+        let content = field.bytes().await.map_err(bad_request)?;
+        let asset = assets::upload(
+            &**storage,
+            &database,
+            account_id.clone().into(),
+            content.to_vec(),
+        )
+        .await
+        .map_err(bad_request)?;
+        uploaded.push(serde_json::json!({
+            "hash": asset.hash,
+            "contentType": asset.content_type,
+            "size": asset.size,
+        }));
+    }
+    Ok(content::Json(serde_json::Value::from(uploaded).to_string()))
+}
+
+/// Download the content of an asset.
+#[rocket::get("/assets/<hash>")]
+pub async fn download<'a>(
+    database: State<'a, Database>,
+    storage: State<'a, Box<dyn Storage>>,
+    hash: String,
+) -> Result<Option<AssetContent>, status::Custom<String>> {
+    let (asset, content) = match assets::download(&**storage, &database, &hash).await {
+        Ok(Some(asset)) => asset,
+        Ok(None) => return Ok(None),
+        Err(error) => {
+            return Err(status::Custom(
+                Status::InternalServerError,
+                error.to_string(),
+            ))
+        }
+    };
+    Ok(Some(AssetContent {
+        content_type: asset.content_type,
+        content,
+    }))
+}
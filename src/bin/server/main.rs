@@ -1,6 +1,7 @@
 #[macro_use]
 extern crate rocket;
 
+mod assets;
 mod fairing;
 use fairing::Cors;
 
@@ -52,14 +53,23 @@ async fn main() {
     dotenv::dotenv().ok();
     env_logger::init();
     let database_url = env::var("DATABASE_URL").unwrap();
+    let database = Database::connect(database_url).unwrap();
+    schema::spawn_jobs(database.clone());
 
     rocket::ignite()
         .attach(Cors)
-        .manage(Database::connect(database_url).unwrap())
+        .manage(database)
         .manage(schema::create())
+        .manage(lib::assets::from_env().unwrap())
         .mount(
             "/",
-            routes![get_graphql_handler, post_graphql_handler, graphiql],
+            routes![
+                get_graphql_handler,
+                post_graphql_handler,
+                graphiql,
+                assets::upload,
+                assets::download,
+            ],
         )
         .launch()
         .await
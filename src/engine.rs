@@ -0,0 +1,277 @@
+use anyhow::anyhow;
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How long a script may run before it is killed.
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// How much memory, in kilobytes, the engine may use while running a script.
+const MEMORY_LIMIT: u64 = 512 * 1024;
+/// How much output is read from a script before the rest is discarded.
+const OUTPUT_LIMIT: u64 = 1024 * 1024;
+
+/// The runner each script is loaded by. It refuses to run directives, so that a script can only
+/// be run through the goals given by the server.
+const RUNNER: &str = include_str!("../engine/runner.pl");
+
+fn scryer_prolog() -> String {
+    env::var("SCRYER_PROLOG").unwrap_or_else(|_| String::from("scryer-prolog"))
+}
+
+fn bubblewrap() -> String {
+    env::var("BWRAP").unwrap_or_else(|_| String::from("bwrap"))
+}
+
+/// A temporary directory holding a script, the goal to run against it and the runner that loads
+/// them. This is the only directory the engine can see besides the system's own programs and
+/// libraries, and it is removed when this is dropped. The goal is kept in a file rather than
+/// passed as an argument, as goals which hold a game's state can outgrow the limit on the size
+/// of a command line.
+struct ScriptDirectory(PathBuf);
+
+impl ScriptDirectory {
+    fn new(script: &str, goal: &str) -> anyhow::Result<Self> {
+        let path = env::temp_dir().join(format!("paper-wars-{}", Uuid::new_v4()));
+        fs::create_dir(&path)?;
+        let directory = Self(path);
+        fs::write(directory.0.join("runner.pl"), RUNNER)?;
+        fs::write(directory.0.join("script.pl"), script)?;
+        fs::write(directory.0.join("goal.pl"), format!("({}).\n", goal))?;
+        Ok(directory)
+    }
+}
+
+impl Drop for ScriptDirectory {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+/// Describes an error reported by the runner. The runner only reports the kind of error, and
+/// anything else it writes is ignored, so nothing a script prints is passed on to users.
+fn describe_error(kind: &str) -> &'static str {
+    match kind {
+        "directive" => "Scripts may not contain directives",
+        "grammar_rule" => "Scripts may not contain grammar rules",
+        "syntax_error" => "The script contains a syntax error",
+        "permission_error" => "The script redefines a built-in predicate",
+        "existence_error" => "The script uses a predicate or file that does not exist",
+        "resource_error" => "The script ran out of memory",
+        "failed" => "The script failed",
+        _ => "The script raised an error",
+    }
+}
+
+/// Reads everything a process writes to a pipe on another thread, so that the process is not
+/// blocked by a full pipe while it is waited on.
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut output = vec![];
+        if let Some(pipe) = pipe {
+            pipe.take(OUTPUT_LIMIT).read_to_end(&mut output).ok();
+        }
+        output
+    })
+}
+
+/// Loads a script and runs a goal against it, returning each line the goal writes to standard
+/// output. Scripts are written by users, so the engine runs in a sandbox with no network, no
+/// access to the server's environment or files, and only a read-only copy of the script. Its
+/// memory is limited and it is killed if it does not finish in time.
+pub fn query(script: &str, goal: &str) -> anyhow::Result<Vec<String>> {
+    let directory = ScriptDirectory::new(script, goal)?;
+    let scryer_prolog = scryer_prolog();
+    let mut command = Command::new(bubblewrap());
+    command
+        .env_clear()
+        .env("PATH", "/usr/local/bin:/usr/bin:/bin")
+        .args(&["--unshare-all", "--die-with-parent", "--new-session"])
+        .args(&["--ro-bind", "/usr", "/usr"])
+        .args(&["--ro-bind-try", "/bin", "/bin"])
+        .args(&["--ro-bind-try", "/lib", "/lib"])
+        .args(&["--ro-bind-try", "/lib64", "/lib64"])
+        .args(&["--proc", "/proc", "--dev", "/dev"])
+        .arg("--ro-bind")
+        .arg(&directory.0)
+        .arg("/script")
+        .args(&["--chdir", "/script"]);
+    if Path::new(&scryer_prolog).is_absolute() {
+        command
+            .arg("--ro-bind")
+            .arg(&scryer_prolog)
+            .arg(&scryer_prolog);
+    }
+    let mut child = command
+        .arg("sh")
+        .arg("-c")
+        .arg(format!("ulimit -v {} && exec \"$0\" \"$@\"", MEMORY_LIMIT))
+        .arg(&scryer_prolog)
+        .arg("runner.pl")
+        .args(&["-g", "run"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + TIMEOUT;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill().ok();
+            child.wait()?;
+            return Err(anyhow!(
+                "The script did not finish within {} seconds",
+                TIMEOUT.as_secs()
+            ));
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    let stdout = stdout
+        .join()
+        .map_err(|_| anyhow!("Failed to read the output of the script"))?;
+    let stderr = stderr
+        .join()
+        .map_err(|_| anyhow!("Failed to read the output of the script"))?;
+
+    let errors = String::from_utf8_lossy(&stderr);
+    if !status.success() || !errors.trim().is_empty() {
+        log::debug!("The engine reported an error: {}", errors.trim());
+        let kind = errors.lines().next().unwrap_or("").trim();
+        return Err(anyhow!("{}", describe_error(kind)));
+    }
+    Ok(String::from_utf8_lossy(&stdout)
+        .lines()
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Checks that a script can be loaded by the engine.
+pub fn check(script: &str) -> anyhow::Result<()> {
+    query(script, "true")?;
+    Ok(())
+}
+
+/// Lists the names of the archetypes referenced by a map script. A map references an
+/// archetype by declaring an `archetype(Name)` fact.
+pub fn referenced_archetypes(script: &str) -> anyhow::Result<Vec<String>> {
+    query(
+        script,
+        "current_predicate(archetype/1) -> forall(archetype(Name), (write(Name), nl)) ; true",
+    )
+}
+
+/// Reads the number of players a map script is designed for, if it declares one with a
+/// `players(Count)` fact.
+pub fn player_count(script: &str) -> anyhow::Result<Option<i32>> {
+    let output = query(
+        script,
+        "current_predicate(players/1) -> (players(Count), write(Count), nl) ; true",
+    )?;
+    match output.first() {
+        Some(count) => Ok(Some(count.trim().parse()?)),
+        None => Ok(None),
+    }
+}
+
+/// Quotes text as a double-quoted Prolog string.
+fn quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+/// Runs the migration hook of an archetype script to transform the state of an entity that
+/// was created with an older version of the archetype. The hook is a `migrate(FromVersion,
+/// State, NewState)` predicate, where the states are JSON strings. If the script does not
+/// define a hook, the state is kept as is.
+pub fn migrate(script: &str, from_version: i32, state: &Value) -> anyhow::Result<Value> {
+    let state = quote(&state.to_string());
+    let output = query(
+        script,
+        &format!(
+            "(current_predicate(migrate/3) -> migrate({}, {}, State) ; State = {}), format(\"~s~n\", [State])",
+            from_version, state, state,
+        ),
+    )?;
+    Ok(serde_json::from_str(&output.join("\n"))?)
+}
+
+/// Runs the departure hook of an archetype script to decide what becomes of an entity when the
+/// player who owns it leaves a game. The hook is a `depart(State, NewState)` predicate, where the
+/// states are JSON strings. If the hook fails, the entity is removed, which is indicated by
+/// returning `None`. If the script does not define a hook, the state is kept as is.
+pub fn depart(script: &str, state: &Value) -> anyhow::Result<Option<Value>> {
+    let state = quote(&state.to_string());
+    let output = query(
+        script,
+        &format!(
+            "(current_predicate(depart/2) -> (depart({}, State) -> format(\"~s~n\", [State]) ; true) ; format(\"~s~n\", [{}]))",
+            state, state,
+        ),
+    )?;
+    if output.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&output.join("\n"))?))
+}
+
+/// Where a player finished in a game, as decided by a map's victory condition. A lower placement
+/// is better, and players who share a placement drew.
+#[derive(Clone, serde::Deserialize)]
+pub struct Placement {
+    pub account: Uuid,
+    pub placement: i32,
+    pub score: Option<f64>,
+}
+
+/// Runs the victory condition of a map script to decide whether a game is over. The condition is
+/// a `victory(Game, Placements)` predicate, where the game is a JSON string holding the game's
+/// state, its remaining players and its entities, and the placements are a JSON string listing
+/// each remaining player's placement and optional score. If the condition fails, or the script
+/// does not define one, the game continues, which is indicated by returning `None`.
+pub fn victory(script: &str, game: &Value) -> anyhow::Result<Option<Vec<Placement>>> {
+    let game = quote(&game.to_string());
+    let output = query(
+        script,
+        &format!(
+            "current_predicate(victory/2), victory({}, Placements) -> format(\"~s~n\", [Placements]) ; true",
+            game,
+        ),
+    )?;
+    if output.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&output.join("\n"))?))
+}
+
+/// Runs the elimination rule of a map script to find the players who have been knocked out of a
+/// game. The rule is an `eliminated(Game, Accounts)` predicate, where the game is a JSON string
+/// holding the game's state, its remaining players and its entities, and the accounts are a JSON
+/// string listing the accounts of the players who are eliminated. If the rule fails, or the
+/// script does not define one, nobody is eliminated.
+pub fn eliminated(script: &str, game: &Value) -> anyhow::Result<Vec<Uuid>> {
+    let game = quote(&game.to_string());
+    let output = query(
+        script,
+        &format!(
+            "current_predicate(eliminated/2), eliminated({}, Accounts) -> format(\"~s~n\", [Accounts]) ; true",
+            game,
+        ),
+    )?;
+    if output.is_empty() {
+        return Ok(vec![]);
+    }
+    Ok(serde_json::from_str(&output.join("\n"))?)
+}
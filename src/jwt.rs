@@ -45,6 +45,37 @@ pub fn decode(jwt: &str) -> anyhow::Result<Uuid> {
     Ok(Uuid::parse_str(&token.claims.sub)?)
 }
 
+/// Invite tokens are issued separately from sign in tokens, so that one cannot be used as the
+/// other.
+const INVITE_ISSUER: &str = "paper-wars-invite";
+
+/// Signs an invite, producing a token which can be shared with anyone, and which may be redeemed
+/// until the invite expires.
+pub fn encode_invite(invite: &data::Invite) -> anyhow::Result<String> {
+    Ok(jsonwebtoken::encode(
+        &Header::default(),
+        &Claims {
+            sub: invite.id.to_string(),
+            iss: String::from(INVITE_ISSUER),
+            iat: invite.created_at.timestamp() as usize,
+            exp: invite.expires_at.timestamp() as usize,
+        },
+        &EncodingKey::from_secret(&secret()),
+    )?)
+}
+
+pub fn decode_invite(token: &str) -> anyhow::Result<Uuid> {
+    let token = jsonwebtoken::decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(&secret()),
+        &Validation {
+            iss: Some(String::from(INVITE_ISSUER)),
+            ..Validation::default()
+        },
+    )?;
+    Ok(Uuid::parse_str(&token.claims.sub)?)
+}
+
 #[derive(Clone, Debug)]
 pub struct AuthenticatedAccount(Uuid);
 
@@ -1,2 +1,5 @@
+pub mod assets;
+pub mod engine;
 pub mod jwt;
+pub mod rating;
 pub mod schema;
@@ -0,0 +1,119 @@
+//! An implementation of the Glicko-2 rating system, as described by Mark Glickman in
+//! <http://www.glicko.net/glicko/glicko2.pdf>. Each finished game is treated as a single
+//! rating period.
+
+use std::f64::consts::PI;
+
+/// Constrains how much a player's volatility may change over time.
+const TAU: f64 = 0.5;
+/// The factor between the Glicko and Glicko-2 scales.
+const SCALE: f64 = 173.7178;
+const CONVERGENCE: f64 = 0.000001;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Default for Rating {
+    /// The rating of a player who has not yet played any games.
+    fn default() -> Self {
+        Self {
+            rating: 1500.0,
+            deviation: 350.0,
+            volatility: 0.06,
+        }
+    }
+}
+
+impl Rating {
+    fn mu(&self) -> f64 {
+        (self.rating - 1500.0) / SCALE
+    }
+
+    fn phi(&self) -> f64 {
+        self.deviation / SCALE
+    }
+
+    /// Computes a player's new rating given the outcome against each opponent they faced, where
+    /// an outcome is 1 for a win, 0.5 for a draw and 0 for a loss. The opponents' ratings should
+    /// be those they held before the game.
+    pub fn update(&self, outcomes: &[(Rating, f64)]) -> Rating {
+        let mu = self.mu();
+        let phi = self.phi();
+        if outcomes.is_empty() {
+            return Rating {
+                deviation: (phi * phi + self.volatility * self.volatility).sqrt() * SCALE,
+                ..*self
+            };
+        }
+
+        let g = |phi: f64| 1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt();
+        let e = |mu_j: f64, phi_j: f64| 1.0 / (1.0 + (-g(phi_j) * (mu - mu_j)).exp());
+        let v = 1.0
+            / outcomes
+                .iter()
+                .map(|(opponent, _)| {
+                    let (mu_j, phi_j) = (opponent.mu(), opponent.phi());
+                    let e = e(mu_j, phi_j);
+                    g(phi_j).powi(2) * e * (1.0 - e)
+                })
+                .sum::<f64>();
+        let improvement: f64 = outcomes
+            .iter()
+            .map(|(opponent, score)| {
+                let (mu_j, phi_j) = (opponent.mu(), opponent.phi());
+                g(phi_j) * (score - e(mu_j, phi_j))
+            })
+            .sum();
+        let delta = v * improvement;
+
+        let volatility = self.next_volatility(phi, v, delta);
+        let phi_star = (phi * phi + volatility * volatility).sqrt();
+        let phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        let mu = mu + phi * phi * improvement;
+        Rating {
+            rating: mu * SCALE + 1500.0,
+            deviation: phi * SCALE,
+            volatility,
+        }
+    }
+
+    /// Finds the new volatility using the Illinois algorithm.
+    fn next_volatility(&self, phi: f64, v: f64, delta: f64) -> f64 {
+        let a = (self.volatility * self.volatility).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            ex * (delta * delta - phi * phi - v - ex) / (2.0 * (phi * phi + v + ex).powi(2))
+                - (x - a) / (TAU * TAU)
+        };
+
+        let mut x_a = a;
+        let mut x_b = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * TAU) < 0.0 {
+                k += 1.0;
+            }
+            a - k * TAU
+        };
+        let mut f_x_a = f(x_a);
+        let mut f_x_b = f(x_b);
+        while (x_b - x_a).abs() > CONVERGENCE {
+            let next = x_a + (x_a - x_b) * f_x_a / (f_x_b - f_x_a);
+            let f_next = f(next);
+            if f_next * f_x_b <= 0.0 {
+                x_a = x_b;
+                f_x_a = f_x_b;
+            } else {
+                f_x_a /= 2.0;
+            }
+            x_b = next;
+            f_x_b = f_next;
+        }
+        (x_a / 2.0).exp()
+    }
+}
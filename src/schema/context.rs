@@ -1,3 +1,4 @@
+use super::loader::{UniverseCounts, UniverseVersionCounts};
 use super::{Database, Loader};
 use anyhow::anyhow;
 use data::*;
@@ -9,19 +10,42 @@ pub struct Context {
     authenticated_account: Arc<RwLock<Option<Uuid>>>,
     account_loader: Loader<Uuid, Account>,
     archetype_loader: Loader<Uuid, Archetype>,
+    archetype_editor_loader: Loader<(Uuid, Uuid), ArchetypeEditor>,
     archetype_version_loader: Loader<(Uuid, i32), ArchetypeVersion>,
+    archetype_version_asset_loader: Loader<(Uuid, i32, String), ArchetypeVersionAsset>,
+    asset_loader: Loader<String, Asset>,
+    block_loader: Loader<(Uuid, Uuid), Block>,
+    change_request_loader: Loader<Uuid, ChangeRequest>,
+    change_request_approval_loader: Loader<(Uuid, Uuid), ChangeRequestApproval>,
+    change_request_archetype_loader: Loader<(Uuid, Uuid), ChangeRequestArchetype>,
+    change_request_comment_loader: Loader<Uuid, ChangeRequestComment>,
+    change_request_map_loader: Loader<(Uuid, Uuid), ChangeRequestMap>,
     contributor_loader: Loader<(Uuid, Uuid), Contributor>,
+    contributor_change_loader: Loader<Uuid, ContributorChange>,
     email_loader: Loader<CiString, Email>,
     entity_loader: Loader<Uuid, Entity>,
+    friendship_loader: Loader<(Uuid, Uuid), Friendship>,
     game_loader: Loader<Uuid, Game>,
+    game_migration_loader: Loader<Uuid, GameMigration>,
+    game_migration_vote_loader: Loader<(Uuid, Uuid), GameMigrationVote>,
+    game_result_loader: Loader<(Uuid, Uuid), GameResult>,
+    invite_loader: Loader<Uuid, Invite>,
     login_loader: Loader<Uuid, Login>,
     map_loader: Loader<Uuid, Map>,
+    map_editor_loader: Loader<(Uuid, Uuid), MapEditor>,
     map_version_loader: Loader<(Uuid, i32), MapVersion>,
+    map_version_asset_loader: Loader<(Uuid, i32, String), MapVersionAsset>,
+    notification_loader: Loader<Uuid, Notification>,
     player_loader: Loader<(Uuid, Uuid), Player>,
+    player_rating_loader: Loader<(Uuid, Uuid), PlayerRating>,
     universe_loader: Loader<Uuid, Universe>,
+    universe_favorite_loader: Loader<(Uuid, Uuid), UniverseFavorite>,
+    universe_rating_loader: Loader<(Uuid, Uuid), UniverseRating>,
+    universe_stats_loader: Loader<Uuid, UniverseCounts>,
     universe_version_loader: Loader<(Uuid, i32), UniverseVersion>,
     universe_version_archetype_loader: Loader<(Uuid, i32, Uuid), UniverseVersionArchetype>,
     universe_version_map_loader: Loader<(Uuid, i32, Uuid), UniverseVersionMap>,
+    universe_version_stats_loader: Loader<(Uuid, i32), UniverseVersionCounts>,
     database: Database,
 }
 
@@ -31,19 +55,42 @@ impl Context {
             authenticated_account: Arc::new(RwLock::new(authenticated_account)),
             account_loader: Loader::new(database.clone()),
             archetype_loader: Loader::new(database.clone()),
+            archetype_editor_loader: Loader::new(database.clone()),
             archetype_version_loader: Loader::new(database.clone()),
+            archetype_version_asset_loader: Loader::new(database.clone()),
+            asset_loader: Loader::new(database.clone()),
+            block_loader: Loader::new(database.clone()),
+            change_request_loader: Loader::new(database.clone()),
+            change_request_approval_loader: Loader::new(database.clone()),
+            change_request_archetype_loader: Loader::new(database.clone()),
+            change_request_comment_loader: Loader::new(database.clone()),
+            change_request_map_loader: Loader::new(database.clone()),
             contributor_loader: Loader::new(database.clone()),
+            contributor_change_loader: Loader::new(database.clone()),
             email_loader: Loader::new(database.clone()),
             entity_loader: Loader::new(database.clone()),
+            friendship_loader: Loader::new(database.clone()),
             game_loader: Loader::new(database.clone()),
+            game_migration_loader: Loader::new(database.clone()),
+            game_migration_vote_loader: Loader::new(database.clone()),
+            game_result_loader: Loader::new(database.clone()),
+            invite_loader: Loader::new(database.clone()),
             login_loader: Loader::new(database.clone()),
             map_loader: Loader::new(database.clone()),
+            map_editor_loader: Loader::new(database.clone()),
             map_version_loader: Loader::new(database.clone()),
+            map_version_asset_loader: Loader::new(database.clone()),
+            notification_loader: Loader::new(database.clone()),
             player_loader: Loader::new(database.clone()),
+            player_rating_loader: Loader::new(database.clone()),
             universe_loader: Loader::new(database.clone()),
+            universe_favorite_loader: Loader::new(database.clone()),
+            universe_rating_loader: Loader::new(database.clone()),
+            universe_stats_loader: Loader::new(database.clone()),
             universe_version_loader: Loader::new(database.clone()),
             universe_version_archetype_loader: Loader::new(database.clone()),
             universe_version_map_loader: Loader::new(database.clone()),
+            universe_version_stats_loader: Loader::new(database.clone()),
             database,
         }
     }
@@ -80,14 +127,54 @@ impl Context {
         &self.archetype_loader
     }
 
+    pub fn archetype_editors(&self) -> &Loader<(Uuid, Uuid), ArchetypeEditor> {
+        &self.archetype_editor_loader
+    }
+
     pub fn archetype_versions(&self) -> &Loader<(Uuid, i32), ArchetypeVersion> {
         &self.archetype_version_loader
     }
 
+    pub fn archetype_version_assets(&self) -> &Loader<(Uuid, i32, String), ArchetypeVersionAsset> {
+        &self.archetype_version_asset_loader
+    }
+
+    pub fn assets(&self) -> &Loader<String, Asset> {
+        &self.asset_loader
+    }
+
+    pub fn blocks(&self) -> &Loader<(Uuid, Uuid), Block> {
+        &self.block_loader
+    }
+
+    pub fn change_requests(&self) -> &Loader<Uuid, ChangeRequest> {
+        &self.change_request_loader
+    }
+
+    pub fn change_request_approvals(&self) -> &Loader<(Uuid, Uuid), ChangeRequestApproval> {
+        &self.change_request_approval_loader
+    }
+
+    pub fn change_request_archetypes(&self) -> &Loader<(Uuid, Uuid), ChangeRequestArchetype> {
+        &self.change_request_archetype_loader
+    }
+
+    pub fn change_request_comments(&self) -> &Loader<Uuid, ChangeRequestComment> {
+        &self.change_request_comment_loader
+    }
+
+    pub fn change_request_maps(&self) -> &Loader<(Uuid, Uuid), ChangeRequestMap> {
+        &self.change_request_map_loader
+    }
+
     pub fn contributors(&self) -> &Loader<(Uuid, Uuid), Contributor> {
         &self.contributor_loader
     }
 
+    pub fn contributor_changes(&self) -> &Loader<Uuid, ContributorChange> {
+        &self.contributor_change_loader
+    }
+
     pub fn emails(&self) -> &Loader<CiString, Email> {
         &self.email_loader
     }
@@ -96,10 +183,30 @@ impl Context {
         &self.entity_loader
     }
 
+    pub fn friendships(&self) -> &Loader<(Uuid, Uuid), Friendship> {
+        &self.friendship_loader
+    }
+
     pub fn games(&self) -> &Loader<Uuid, Game> {
         &self.game_loader
     }
 
+    pub fn game_migrations(&self) -> &Loader<Uuid, GameMigration> {
+        &self.game_migration_loader
+    }
+
+    pub fn game_migration_votes(&self) -> &Loader<(Uuid, Uuid), GameMigrationVote> {
+        &self.game_migration_vote_loader
+    }
+
+    pub fn game_results(&self) -> &Loader<(Uuid, Uuid), GameResult> {
+        &self.game_result_loader
+    }
+
+    pub fn invites(&self) -> &Loader<Uuid, Invite> {
+        &self.invite_loader
+    }
+
     pub fn logins(&self) -> &Loader<Uuid, Login> {
         &self.login_loader
     }
@@ -108,18 +215,46 @@ impl Context {
         &self.map_loader
     }
 
+    pub fn map_editors(&self) -> &Loader<(Uuid, Uuid), MapEditor> {
+        &self.map_editor_loader
+    }
+
     pub fn map_versions(&self) -> &Loader<(Uuid, i32), MapVersion> {
         &self.map_version_loader
     }
 
+    pub fn map_version_assets(&self) -> &Loader<(Uuid, i32, String), MapVersionAsset> {
+        &self.map_version_asset_loader
+    }
+
+    pub fn notifications(&self) -> &Loader<Uuid, Notification> {
+        &self.notification_loader
+    }
+
     pub fn players(&self) -> &Loader<(Uuid, Uuid), Player> {
         &self.player_loader
     }
 
+    pub fn player_ratings(&self) -> &Loader<(Uuid, Uuid), PlayerRating> {
+        &self.player_rating_loader
+    }
+
     pub fn universes(&self) -> &Loader<Uuid, Universe> {
         &self.universe_loader
     }
 
+    pub fn universe_favorites(&self) -> &Loader<(Uuid, Uuid), UniverseFavorite> {
+        &self.universe_favorite_loader
+    }
+
+    pub fn universe_ratings(&self) -> &Loader<(Uuid, Uuid), UniverseRating> {
+        &self.universe_rating_loader
+    }
+
+    pub fn universe_stats(&self) -> &Loader<Uuid, UniverseCounts> {
+        &self.universe_stats_loader
+    }
+
     pub fn universe_versions(&self) -> &Loader<(Uuid, i32), UniverseVersion> {
         &self.universe_version_loader
     }
@@ -133,6 +268,10 @@ impl Context {
     pub fn universe_version_maps(&self) -> &Loader<(Uuid, i32, Uuid), UniverseVersionMap> {
         &self.universe_version_map_loader
     }
+
+    pub fn universe_version_stats(&self) -> &Loader<(Uuid, i32), UniverseVersionCounts> {
+        &self.universe_version_stats_loader
+    }
 }
 
 impl juniper::Context for Context {}
@@ -0,0 +1,136 @@
+use super::{Database, Mutation};
+use chrono::Utc;
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use std::time::Duration;
+
+/// How often each server checks for jobs that are due.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// How long to wait before retrying a job that failed for the first time. Each further failure
+/// doubles the wait, up to the job's usual interval.
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// A piece of maintenance which is run periodically. Each job manages its own transactions, so
+/// that it may do work such as running scripts outside of them, and returns the number of things
+/// it acted on, for logging.
+struct Job {
+    name: &'static str,
+    interval: Duration,
+    run: fn(&Mutation, &Database) -> anyhow::Result<usize>,
+}
+
+const JOBS: &[Job] = &[
+    Job {
+        name: "expire_turns",
+        interval: Duration::from_secs(30),
+        run: Mutation::expire_turns,
+    },
+    Job {
+        name: "count_map_players",
+        interval: Duration::from_secs(60 * 10),
+        run: Mutation::count_map_players,
+    },
+    Job {
+        name: "purge_unverified_emails",
+        interval: Duration::from_secs(60 * 60),
+        run: |mutation, database| {
+            database.transaction(|conn| mutation.purge_unverified_emails(conn))
+        },
+    },
+    Job {
+        name: "expire_invitations",
+        interval: Duration::from_secs(60 * 60),
+        run: |mutation, database| database.transaction(|conn| mutation.expire_invitations(conn)),
+    },
+    Job {
+        name: "close_abandoned_lobbies",
+        interval: Duration::from_secs(60 * 60 * 6),
+        run: |mutation, database| {
+            database.transaction(|conn| mutation.close_abandoned_lobbies(conn))
+        },
+    },
+];
+
+/// Starts a background task which runs each job whenever it is due. The schedule is kept in the
+/// jobs table, and a job's row stays locked while it runs, so when several servers share a
+/// database each job is only run by one of them at a time.
+pub fn spawn_jobs(database: Database) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            let database = database.clone();
+            let result = tokio::task::spawn_blocking(move || run_due_jobs(&database)).await;
+            if let Err(error) = result {
+                log::error!("Scheduled jobs panicked: {}", error);
+            }
+        }
+    });
+}
+
+/// Runs each job that is due. A job that cannot be run is logged and skipped, so that it does
+/// not stop the jobs after it from running.
+fn run_due_jobs(database: &Database) {
+    for job in JOBS {
+        let result = database.transaction(|conn| {
+            insert_into(jobs::table)
+                .values(jobs::name.eq(job.name))
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+            let row: Option<data::Job> = jobs::table
+                .find(job.name)
+                .filter(jobs::run_at.le(now))
+                .for_update()
+                .skip_locked()
+                .get_result(conn)
+                .optional()?;
+            let row = match row {
+                Some(row) => row,
+                None => return Ok(()),
+            };
+
+            // The job runs in its own transactions while this one keeps its row locked, so a
+            // failure undoes only the job's own work and the failure can still be recorded.
+            let result = (job.run)(&Mutation, database);
+            match result {
+                Ok(count) => {
+                    if count > 0 {
+                        log::info!("Job {} acted on {} items", job.name, count);
+                    }
+                    update(&row)
+                        .set((
+                            jobs::run_at.eq(Utc::now() + chrono::Duration::from_std(job.interval)?),
+                            jobs::attempts.eq(0),
+                            jobs::last_run_at.eq(now),
+                            jobs::last_error.eq(None::<String>),
+                        ))
+                        .execute(conn)?;
+                }
+                Err(error) => {
+                    log::warn!(
+                        "Job {} failed (attempt {}): {}",
+                        job.name,
+                        row.attempts + 1,
+                        error
+                    );
+                    let delay = RETRY_DELAY
+                        .checked_mul(2u32.saturating_pow(row.attempts as u32))
+                        .unwrap_or(job.interval)
+                        .min(job.interval);
+                    update(&row)
+                        .set((
+                            jobs::run_at.eq(Utc::now() + chrono::Duration::from_std(delay)?),
+                            jobs::attempts.eq(row.attempts + 1),
+                            jobs::last_error.eq(error.to_string()),
+                        ))
+                        .execute(conn)?;
+                }
+            }
+            Ok(())
+        });
+        if let Err(error) = result {
+            log::error!("Failed to run job {}: {}", job.name, error);
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use super::Loader;
+use data::ArchetypeEditor;
+use uuid::Uuid;
+
+batch_fn!(archetype_editors => ArchetypeEditor { archetype_id: Uuid, account_id: Uuid });
+
+impl Loader<(Uuid, Uuid), ArchetypeEditor> {
+    join!(archetype_editors => for_archetype(archetype_id: Uuid) -> ArchetypeEditor);
+}
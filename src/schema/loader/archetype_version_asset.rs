@@ -0,0 +1,9 @@
+use super::Loader;
+use data::ArchetypeVersionAsset;
+use uuid::Uuid;
+
+batch_fn!(archetype_version_assets => ArchetypeVersionAsset { archetype_id: Uuid, archetype_version: i32, name: String });
+
+impl Loader<(Uuid, i32, String), ArchetypeVersionAsset> {
+    join!(archetype_version_assets => for_archetype_version(archetype_id: Uuid, archetype_version: i32) -> ArchetypeVersionAsset);
+}
@@ -0,0 +1,3 @@
+use data::Asset;
+
+batch_fn!(assets => Asset { hash: String });
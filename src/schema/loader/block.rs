@@ -0,0 +1,9 @@
+use super::Loader;
+use data::Block;
+use uuid::Uuid;
+
+batch_fn!(blocks => Block { account_id: Uuid, blocked_id: Uuid });
+
+impl Loader<(Uuid, Uuid), Block> {
+    join!(blocks => for_account(account_id: Uuid) -> Block);
+}
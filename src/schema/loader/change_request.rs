@@ -0,0 +1,9 @@
+use super::Loader;
+use data::ChangeRequest;
+use uuid::Uuid;
+
+batch_fn!(change_requests => ChangeRequest { id: Uuid });
+
+impl Loader<Uuid, ChangeRequest> {
+    join!(change_requests => for_universe(universe_id: Uuid) -> ChangeRequest);
+}
@@ -0,0 +1,9 @@
+use super::Loader;
+use data::ChangeRequestApproval;
+use uuid::Uuid;
+
+batch_fn!(change_request_approvals => ChangeRequestApproval { change_request_id: Uuid, account_id: Uuid });
+
+impl Loader<(Uuid, Uuid), ChangeRequestApproval> {
+    join!(change_request_approvals => for_change_request(change_request_id: Uuid) -> ChangeRequestApproval);
+}
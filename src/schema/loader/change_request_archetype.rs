@@ -0,0 +1,9 @@
+use super::Loader;
+use data::ChangeRequestArchetype;
+use uuid::Uuid;
+
+batch_fn!(change_request_archetypes => ChangeRequestArchetype { change_request_id: Uuid, archetype_id: Uuid });
+
+impl Loader<(Uuid, Uuid), ChangeRequestArchetype> {
+    join!(change_request_archetypes => for_change_request(change_request_id: Uuid) -> ChangeRequestArchetype);
+}
@@ -0,0 +1,9 @@
+use super::Loader;
+use data::ChangeRequestComment;
+use uuid::Uuid;
+
+batch_fn!(change_request_comments => ChangeRequestComment { id: Uuid });
+
+impl Loader<Uuid, ChangeRequestComment> {
+    join!(change_request_comments => for_change_request(change_request_id: Uuid) -> ChangeRequestComment);
+}
@@ -0,0 +1,9 @@
+use super::Loader;
+use data::ChangeRequestMap;
+use uuid::Uuid;
+
+batch_fn!(change_request_maps => ChangeRequestMap { change_request_id: Uuid, map_id: Uuid });
+
+impl Loader<(Uuid, Uuid), ChangeRequestMap> {
+    join!(change_request_maps => for_change_request(change_request_id: Uuid) -> ChangeRequestMap);
+}
@@ -0,0 +1,9 @@
+use super::Loader;
+use data::ContributorChange;
+use uuid::Uuid;
+
+batch_fn!(contributor_changes => ContributorChange { id: Uuid });
+
+impl Loader<Uuid, ContributorChange> {
+    join!(contributor_changes => for_universe(universe_id: Uuid) -> ContributorChange);
+}
@@ -0,0 +1,10 @@
+use super::Loader;
+use data::Friendship;
+use uuid::Uuid;
+
+batch_fn!(friendships => Friendship { requester_id: Uuid, addressee_id: Uuid });
+
+impl Loader<(Uuid, Uuid), Friendship> {
+    join!(friendships => for_requester(requester_id: Uuid) -> Friendship);
+    join!(friendships => for_addressee(addressee_id: Uuid) -> Friendship);
+}
@@ -0,0 +1,4 @@
+use data::GameMigration;
+use uuid::Uuid;
+
+batch_fn!(game_migrations => GameMigration { game_id: Uuid });
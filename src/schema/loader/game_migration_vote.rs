@@ -0,0 +1,9 @@
+use super::Loader;
+use data::GameMigrationVote;
+use uuid::Uuid;
+
+batch_fn!(game_migration_votes => GameMigrationVote { game_id: Uuid, account_id: Uuid });
+
+impl Loader<(Uuid, Uuid), GameMigrationVote> {
+    join!(game_migration_votes => for_game(game_id: Uuid) -> GameMigrationVote);
+}
@@ -0,0 +1,9 @@
+use super::Loader;
+use data::GameResult;
+use uuid::Uuid;
+
+batch_fn!(game_results => GameResult { game_id: Uuid, account_id: Uuid });
+
+impl Loader<(Uuid, Uuid), GameResult> {
+    join!(game_results => for_game(game_id: Uuid) -> GameResult);
+}
@@ -0,0 +1,10 @@
+use super::Loader;
+use data::Invite;
+use uuid::Uuid;
+
+batch_fn!(invites => Invite { id: Uuid });
+
+impl Loader<Uuid, Invite> {
+    join!(invites => for_game(game_id: Uuid) -> Invite);
+    join!(invites => for_universe(universe_id: Uuid) -> Invite);
+}
@@ -0,0 +1,9 @@
+use super::Loader;
+use data::MapEditor;
+use uuid::Uuid;
+
+batch_fn!(map_editors => MapEditor { map_id: Uuid, account_id: Uuid });
+
+impl Loader<(Uuid, Uuid), MapEditor> {
+    join!(map_editors => for_map(map_id: Uuid) -> MapEditor);
+}
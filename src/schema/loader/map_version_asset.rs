@@ -0,0 +1,9 @@
+use super::Loader;
+use data::MapVersionAsset;
+use uuid::Uuid;
+
+batch_fn!(map_version_assets => MapVersionAsset { map_id: Uuid, map_version: i32, name: String });
+
+impl Loader<(Uuid, i32, String), MapVersionAsset> {
+    join!(map_version_assets => for_map_version(map_id: Uuid, map_version: i32) -> MapVersionAsset);
+}
@@ -12,18 +12,42 @@ mod macros;
 
 mod account;
 mod archetype;
+mod archetype_editor;
 mod archetype_version;
+mod archetype_version_asset;
+mod asset;
+mod block;
+mod change_request;
+mod change_request_approval;
+mod change_request_archetype;
+mod change_request_comment;
+mod change_request_map;
 mod contributor;
+mod contributor_change;
 mod email;
 mod entity;
+mod friendship;
+mod game_migration;
+mod game_migration_vote;
+mod game_result;
+mod invite;
 mod login;
 mod map;
+mod map_editor;
 mod map_version;
+mod map_version_asset;
+mod notification;
 mod player;
+mod player_rating;
+mod universe_favorite;
+mod universe_rating;
+mod universe_stats;
 mod universe_version;
 mod universe_version_archetype;
 mod universe_version_map;
 
+pub use universe_stats::{UniverseCounts, UniverseVersionCounts};
+
 pub struct Loader<K, T>
 where
     K: Hash + Eq + Clone + Debug,
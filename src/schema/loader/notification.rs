@@ -0,0 +1,22 @@
+use super::Loader;
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+batch_fn!(notifications => Notification { id: Uuid });
+
+impl Loader<Uuid, Notification> {
+    /// Counts the notifications in an account's inbox which have not yet been read.
+    pub fn count_unread(&self, account_id: Uuid) -> anyhow::Result<i32> {
+        let count = tokio::task::block_in_place(|| -> anyhow::Result<i64> {
+            let conn = self.database.connection()?;
+            Ok(notifications::table
+                .select(count_star())
+                .filter(notifications::account_id.eq(account_id))
+                .filter(notifications::read_at.is_null())
+                .get_result(&conn)?)
+        })?;
+        Ok(count as i32)
+    }
+}
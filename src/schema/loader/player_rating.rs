@@ -0,0 +1,21 @@
+use super::Loader;
+use data::{player_ratings, PlayerRating};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+batch_fn!(player_ratings => PlayerRating { universe_id: Uuid, account_id: Uuid });
+
+impl Loader<(Uuid, Uuid), PlayerRating> {
+    /// An account's rating in each universe it has finished a game in, highest first.
+    pub fn for_account(&self, account_id: Uuid) -> anyhow::Result<Vec<PlayerRating>> {
+        let ratings = tokio::task::block_in_place(|| -> anyhow::Result<Vec<PlayerRating>> {
+            let conn = self.database.connection()?;
+            Ok(player_ratings::table
+                .filter(player_ratings::account_id.eq(account_id))
+                .order_by(player_ratings::rating.desc())
+                .load(&conn)?)
+        })?;
+        self.prime_many(ratings.clone());
+        Ok(ratings)
+    }
+}
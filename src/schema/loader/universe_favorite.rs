@@ -0,0 +1,4 @@
+use data::UniverseFavorite;
+use uuid::Uuid;
+
+batch_fn!(universe_favorites => UniverseFavorite { universe_id: Uuid, account_id: Uuid });
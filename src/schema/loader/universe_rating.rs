@@ -0,0 +1,4 @@
+use data::UniverseRating;
+use uuid::Uuid;
+
+batch_fn!(universe_ratings => UniverseRating { universe_id: Uuid, account_id: Uuid });
@@ -0,0 +1,125 @@
+use super::Database;
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How much activity there has been in a universe. These are counted for a whole batch of
+/// universes at once.
+#[derive(Clone, Debug, Default)]
+pub struct UniverseCounts {
+    pub games_played: i64,
+    pub active_games: i64,
+    pub unique_players: i64,
+    pub favorites: i64,
+    pub ratings: i64,
+    pub rating_total: i64,
+}
+
+/// How much a version of a universe has been played.
+#[derive(Clone, Debug, Default)]
+pub struct UniverseVersionCounts {
+    pub games_played: i64,
+}
+
+impl dataloader::sync::BatchFn<Uuid, Option<UniverseCounts>> for Database {
+    fn load(&self, keys: &[Uuid]) -> HashMap<Uuid, Option<UniverseCounts>> {
+        let counts = self.connection().and_then(|conn| -> anyhow::Result<_> {
+            let games_played: Vec<(Uuid, i64)> = games::table
+                .group_by(games::universe_id)
+                .select((games::universe_id, count_star()))
+                .filter(games::universe_id.eq_any(keys))
+                .filter(games::ended_at.is_not_null())
+                .load(&conn)?;
+            let active_games: Vec<(Uuid, i64)> = games::table
+                .group_by(games::universe_id)
+                .select((games::universe_id, count_star()))
+                .filter(games::universe_id.eq_any(keys))
+                .filter(games::ended_at.is_null())
+                .load(&conn)?;
+            let unique_players: Vec<(Uuid, i64)> = players::table
+                .inner_join(games::table)
+                .group_by(games::universe_id)
+                .select((games::universe_id, count_distinct(players::account_id)))
+                .filter(games::universe_id.eq_any(keys))
+                .filter(players::engagement.ne(PlayerEngagement::Pending))
+                .filter(players::engagement.ne(PlayerEngagement::Declined))
+                .load(&conn)?;
+            let favorites: Vec<(Uuid, i64)> = universe_favorites::table
+                .group_by(universe_favorites::universe_id)
+                .select((universe_favorites::universe_id, count_star()))
+                .filter(universe_favorites::universe_id.eq_any(keys))
+                .load(&conn)?;
+            let ratings: Vec<(Uuid, i64, Option<i64>)> = universe_ratings::table
+                .group_by(universe_ratings::universe_id)
+                .select((
+                    universe_ratings::universe_id,
+                    count_star(),
+                    sum(universe_ratings::rating),
+                ))
+                .filter(universe_ratings::universe_id.eq_any(keys))
+                .load(&conn)?;
+
+            let mut counts: HashMap<Uuid, UniverseCounts> = keys
+                .iter()
+                .map(|key| (*key, UniverseCounts::default()))
+                .collect();
+            for (universe_id, count) in games_played {
+                counts.get_mut(&universe_id).unwrap().games_played = count;
+            }
+            for (universe_id, count) in active_games {
+                counts.get_mut(&universe_id).unwrap().active_games = count;
+            }
+            for (universe_id, count) in unique_players {
+                counts.get_mut(&universe_id).unwrap().unique_players = count;
+            }
+            for (universe_id, count) in favorites {
+                counts.get_mut(&universe_id).unwrap().favorites = count;
+            }
+            for (universe_id, count, total) in ratings {
+                let universe_counts = counts.get_mut(&universe_id).unwrap();
+                universe_counts.ratings = count;
+                universe_counts.rating_total = total.unwrap_or(0);
+            }
+            Ok(counts)
+        });
+        match counts {
+            Ok(counts) => counts
+                .into_iter()
+                .map(|(key, counts)| (key, Some(counts)))
+                .collect(),
+            Err(..) => keys.iter().map(|key| (*key, None)).collect(),
+        }
+    }
+}
+
+impl dataloader::sync::BatchFn<(Uuid, i32), Option<UniverseVersionCounts>> for Database {
+    fn load(&self, keys: &[(Uuid, i32)]) -> HashMap<(Uuid, i32), Option<UniverseVersionCounts>> {
+        let universe_ids: Vec<Uuid> = keys.iter().map(|(universe_id, _)| *universe_id).collect();
+        let games_played = self.connection().and_then(|conn| -> anyhow::Result<_> {
+            let games_played: Vec<(Uuid, i32, i64)> = games::table
+                .group_by((games::universe_id, games::universe_version))
+                .select((games::universe_id, games::universe_version, count_star()))
+                .filter(games::universe_id.eq_any(universe_ids))
+                .filter(games::ended_at.is_not_null())
+                .load(&conn)?;
+            Ok(games_played)
+        });
+        match games_played {
+            Ok(games_played) => {
+                let mut counts: HashMap<(Uuid, i32), Option<UniverseVersionCounts>> = keys
+                    .iter()
+                    .map(|key| (*key, Some(UniverseVersionCounts::default())))
+                    .collect();
+                for (universe_id, version, count) in games_played {
+                    if let Some(Some(version_counts)) = counts.get_mut(&(universe_id, version)) {
+                        version_counts.games_played = count;
+                    }
+                }
+                counts
+            }
+            Err(..) => keys.iter().map(|key| (*key, None)).collect(),
+        }
+    }
+}
@@ -23,7 +23,9 @@ impl Loader<(Uuid, i32), UniverseVersion> {
                     .filter(universe_versions::universe_id.eq(universe_id))
                     .into_boxed();
                 if !unreleased {
-                    max_version = max_version.filter(universe_versions::released_at.is_not_null());
+                    max_version = max_version
+                        .filter(universe_versions::released_at.is_not_null())
+                        .filter(universe_versions::yanked_at.is_null());
                 }
                 match max_version.get_result::<Option<i32>>(&conn)? {
                     Some(max_version) => Ok(universe_versions::table
@@ -40,4 +42,17 @@ impl Loader<(Uuid, i32), UniverseVersion> {
         }
         Ok(version)
     }
+
+    /// Finds the latest released version of a universe that came before the given version.
+    pub fn load_previous_release(
+        &self,
+        universe_id: Uuid,
+        version: i32,
+    ) -> Option<UniverseVersion> {
+        self.for_universe(&universe_id)
+            .into_iter()
+            .filter(|release| release.released_at.is_some())
+            .filter(|release| release.version < version)
+            .max_by_key(|release| release.version)
+    }
 }
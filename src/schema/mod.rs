@@ -2,15 +2,20 @@ use juniper::RootNode;
 
 mod context;
 mod database;
+mod jobs;
 mod loader;
 mod mutation;
 mod query;
+mod revision;
 mod subscription;
+mod time_control;
+mod validation;
 
 use loader::Loader;
 
 pub use context::Context;
 pub use database::Database;
+pub use jobs::spawn_jobs;
 pub use mutation::Mutation;
 pub use query::Query;
 pub use subscription::Subscription;
@@ -1,4 +1,5 @@
 use super::{ArchetypeVersion, Context, Mutation};
+use crate::schema::query;
 use data::*;
 use diesel::dsl::*;
 use diesel::prelude::*;
@@ -8,26 +9,70 @@ use uuid::Uuid;
 pub struct CreateArchetype {
     name: String,
     universe: Uuid,
+    display_name: Option<String>,
+    description: Option<String>,
+    tags: Option<Vec<String>>,
 }
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct UpdateArchetype {
     id: Uuid,
     script: String,
+    revision: Option<String>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct UpdateArchetypeDetails {
+    id: Uuid,
+    name: Option<String>,
+    display_name: Option<String>,
+    description: Option<String>,
+    tags: Option<Vec<String>>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct DeleteArchetype {
+    id: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RevertArchetype {
+    id: Uuid,
+    to_version: i32,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct SetArchetypeAsset {
+    id: Uuid,
+    name: String,
+    asset: Option<String>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct SetArchetypeEditors {
+    id: Uuid,
+    editors: Option<Vec<Uuid>>,
 }
 
 impl Mutation {
     pub(super) fn create_archetype(
         &self,
         context: &Context,
-        CreateArchetype { name, universe }: CreateArchetype,
+        CreateArchetype {
+            name,
+            universe,
+            display_name,
+            description,
+            tags,
+        }: CreateArchetype,
     ) -> anyhow::Result<ArchetypeVersion> {
         let account_id = context.try_authenticated_account()?;
         let (archetype, archetype_version) = context.transaction(|conn| {
             self.assert_universe_contributor(universe, account_id, conn)?;
             let existing = archetypes::table
                 .filter(archetypes::universe_id.eq(&universe))
-                .filter(archetypes::name.eq(&name));
+                .filter(archetypes::name.eq(&name))
+                .filter(archetypes::deleted_at.is_null());
             let archetype_exists: bool = select(exists(existing)).get_result(conn)?;
             anyhow::ensure!(
                 !archetype_exists,
@@ -38,6 +83,9 @@ impl Mutation {
                 .values((
                     archetypes::name.eq(&name),
                     archetypes::universe_id.eq(&universe),
+                    archetypes::display_name.eq(&display_name),
+                    archetypes::description.eq(&description),
+                    archetypes::tags.eq(tags.unwrap_or_default()),
                 ))
                 .returning(archetypes::all_columns)
                 .get_result(conn)?;
@@ -59,51 +107,236 @@ impl Mutation {
         Ok(query)
     }
 
-    #[rustfmt::skip]
     pub(super) fn update_archetype(
         &self,
         context: &Context,
-        UpdateArchetype { id, script }: UpdateArchetype,
+        UpdateArchetype {
+            id,
+            script,
+            revision,
+        }: UpdateArchetype,
     ) -> anyhow::Result<ArchetypeVersion> {
         let account_id = context.try_authenticated_account()?;
         let archetype_version: data::ArchetypeVersion = context.transaction(|conn| {
             let archetype = archetypes::table
                 .filter(archetypes::id.eq(id))
+                .for_update()
                 .get_result::<Archetype>(conn)?;
-            self.assert_universe_contributor(archetype.universe_id, account_id, conn)?;
-            let most_recent_version = self.archetype_current_version(archetype.id, conn)?;
-            let same_universe_version = universe_versions::universe_id.eq(universe_version_archetypes::universe_id)
-                .and(universe_versions::version.eq(universe_version_archetypes::universe_version));
-            let version_in_use = universe_version_archetypes::table
-                .inner_join(universe_versions::table.on(same_universe_version))
-                .filter(universe_version_archetypes::archetype_id.eq(archetype.id))
-                .filter(universe_version_archetypes::archetype_version.eq(most_recent_version))
-                .filter(universe_versions::released_at.is_not_null());
-            let version_in_use = select(exists(version_in_use)).get_result(conn)?;
-            let archetype_version: data::ArchetypeVersion = if version_in_use {
-                insert_into(archetype_versions::table)
-                    .values((
-                        archetype_versions::archetype_id.eq(archetype.id),
-                        archetype_versions::version.eq(most_recent_version + 1),
-                        archetype_versions::script.eq(&script),
-                    ))
-                    .returning(archetype_versions::all_columns)
-                    .get_result(conn)?
-            } else {
-                update(archetype_versions::table)
-                    .set(archetype_versions::script.eq(&script))
-                    .filter(archetype_versions::archetype_id.eq(id))
-                    .filter(archetype_versions::version.eq(most_recent_version))
-                    .returning(archetype_versions::all_columns)
-                    .get_result(conn)?
-            };
+            self.assert_archetype_editor(&archetype, account_id, conn)?;
+            if let Some(revision) = &revision {
+                self.assert_archetype_revision(archetype.id, revision, conn)?;
+            }
+            self.write_archetype_script(&archetype, &script, conn)
+        })?;
+
+        let query =
+            ArchetypeVersion::new(archetype_version.archetype_id, archetype_version.version);
+        context.archetype_versions().prime(archetype_version);
+        Ok(query)
+    }
+
+    pub(super) fn update_archetype_details(
+        &self,
+        context: &Context,
+        UpdateArchetypeDetails {
+            id,
+            name,
+            display_name,
+            description,
+            tags,
+        }: UpdateArchetypeDetails,
+    ) -> anyhow::Result<query::Archetype> {
+        let account_id = context.try_authenticated_account()?;
+        let archetype = context.transaction(|conn| {
+            let archetype = archetypes::table
+                .filter(archetypes::id.eq(id))
+                .for_update()
+                .get_result::<Archetype>(conn)?;
+            anyhow::ensure!(
+                archetype.deleted_at.is_none(),
+                "This archetype ({}) has been deleted",
+                id,
+            );
+            self.assert_archetype_editor(&archetype, account_id, conn)?;
+            if let Some(name) = name {
+                let existing = archetypes::table
+                    .filter(archetypes::universe_id.eq(archetype.universe_id))
+                    .filter(archetypes::name.eq(&name))
+                    .filter(archetypes::id.ne(id))
+                    .filter(archetypes::deleted_at.is_null());
+                let archetype_exists: bool = select(exists(existing)).get_result(conn)?;
+                anyhow::ensure!(
+                    !archetype_exists,
+                    "An archetype with this name ({}) already exists",
+                    &name,
+                );
+                update(&archetype)
+                    .set(archetypes::name.eq(name))
+                    .execute(conn)?;
+            }
+            if let Some(display_name) = display_name {
+                let display_name = Some(display_name).filter(|name| !name.is_empty());
+                update(&archetype)
+                    .set(archetypes::display_name.eq(display_name))
+                    .execute(conn)?;
+            }
+            if let Some(description) = description {
+                let description = Some(description).filter(|description| !description.is_empty());
+                update(&archetype)
+                    .set(archetypes::description.eq(description))
+                    .execute(conn)?;
+            }
+            if let Some(tags) = tags {
+                update(&archetype)
+                    .set(archetypes::tags.eq(tags))
+                    .execute(conn)?;
+            }
+            let archetype: data::Archetype = archetypes::table.find(id).get_result(conn)?;
+            Ok(archetype)
+        })?;
+
+        let query = query::Archetype::new(archetype.id);
+        context.archetypes().prime(archetype);
+        Ok(query)
+    }
+
+    pub(super) fn delete_archetype(
+        &self,
+        context: &Context,
+        DeleteArchetype { id }: DeleteArchetype,
+    ) -> anyhow::Result<()> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            let archetype = archetypes::table
+                .filter(archetypes::id.eq(id))
+                .for_update()
+                .get_result::<Archetype>(conn)?;
+            anyhow::ensure!(
+                archetype.deleted_at.is_none(),
+                "This archetype ({}) has already been deleted",
+                id,
+            );
+            self.assert_universe_maintainer(archetype.universe_id, account_id, conn)?;
+            let proposed_in_open_change_request = change_request_archetypes::table
+                .inner_join(change_requests::table)
+                .filter(change_request_archetypes::archetype_id.eq(id))
+                .filter(change_requests::state.eq(ChangeRequestState::Open));
+            let proposed_in_open_change_request: bool =
+                select(exists(proposed_in_open_change_request)).get_result(conn)?;
+            anyhow::ensure!(
+                !proposed_in_open_change_request,
+                "This archetype ({}) has changes proposed in an open change request, which must be closed before it can be deleted",
+                id,
+            );
             let universe_version = self.unreleased_universe_version(archetype.universe_id, conn)?;
-            update(universe_version_archetypes::table)
+            let unreleased_pin = universe_version_archetypes::table
                 .filter(universe_version_archetypes::universe_id.eq(universe_version.universe_id))
                 .filter(universe_version_archetypes::universe_version.eq(universe_version.version))
-                .filter(universe_version_archetypes::archetype_id.eq(archetype_version.archetype_id))
-                .set(universe_version_archetypes::archetype_version.eq(archetype_version.version))
+                .filter(universe_version_archetypes::archetype_id.eq(id));
+            delete(unreleased_pin).execute(conn)?;
+            delete(
+                change_request_archetypes::table
+                    .filter(change_request_archetypes::archetype_id.eq(id)),
+            )
+            .execute(conn)?;
+
+            if self.archetype_released(id, conn)? {
+                // Released versions of the universe still use this archetype, so it must be kept.
+                update(&archetype)
+                    .set(archetypes::deleted_at.eq(now))
+                    .execute(conn)?;
+            } else {
+                delete(archetype_editors::table.filter(archetype_editors::archetype_id.eq(id)))
+                    .execute(conn)?;
+                delete(
+                    universe_version_archetypes::table
+                        .filter(universe_version_archetypes::archetype_id.eq(id)),
+                )
+                .execute(conn)?;
+                delete(
+                    archetype_version_assets::table
+                        .filter(archetype_version_assets::archetype_id.eq(id)),
+                )
                 .execute(conn)?;
+                delete(archetype_versions::table.filter(archetype_versions::archetype_id.eq(id)))
+                    .execute(conn)?;
+                delete(&archetype).execute(conn)?;
+            }
+            Ok(())
+        })
+    }
+
+    pub(super) fn revert_archetype(
+        &self,
+        context: &Context,
+        RevertArchetype { id, to_version }: RevertArchetype,
+    ) -> anyhow::Result<ArchetypeVersion> {
+        let account_id = context.try_authenticated_account()?;
+        let archetype_version: data::ArchetypeVersion = context.transaction(|conn| {
+            let archetype = archetypes::table
+                .filter(archetypes::id.eq(id))
+                .for_update()
+                .get_result::<Archetype>(conn)?;
+            self.assert_archetype_editor(&archetype, account_id, conn)?;
+            let script: String = archetype_versions::table
+                .select(archetype_versions::script)
+                .filter(archetype_versions::archetype_id.eq(id))
+                .filter(archetype_versions::version.eq(to_version))
+                .get_result(conn)
+                .optional()?
+                .ok_or_else(|| {
+                    anyhow::anyhow!("This archetype ({}) has no version {}", id, to_version)
+                })?;
+            self.write_archetype_script(&archetype, &script, conn)
+        })?;
+
+        let query =
+            ArchetypeVersion::new(archetype_version.archetype_id, archetype_version.version);
+        context.archetype_versions().prime(archetype_version);
+        Ok(query)
+    }
+
+    pub(super) fn set_archetype_asset(
+        &self,
+        context: &Context,
+        SetArchetypeAsset { id, name, asset }: SetArchetypeAsset,
+    ) -> anyhow::Result<ArchetypeVersion> {
+        let account_id = context.try_authenticated_account()?;
+        let archetype_version: data::ArchetypeVersion = context.transaction(|conn| {
+            let archetype = archetypes::table
+                .filter(archetypes::id.eq(id))
+                .for_update()
+                .get_result::<Archetype>(conn)?;
+            self.assert_archetype_editor(&archetype, account_id, conn)?;
+            if let Some(hash) = &asset {
+                let asset_exists: bool =
+                    select(exists(assets::table.find(hash))).get_result(conn)?;
+                anyhow::ensure!(asset_exists, "This asset ({}) has not been uploaded", hash);
+            }
+            // Rewriting the current script moves to a new version if the current one has been
+            // released, so that released versions keep the assets they were released with.
+            let most_recent_version = self.archetype_current_version(id, conn)?;
+            let script: String = archetype_versions::table
+                .find((id, most_recent_version))
+                .select(archetype_versions::script)
+                .get_result(conn)?;
+            let archetype_version = self.write_archetype_script(&archetype, &script, conn)?;
+
+            let existing = archetype_version_assets::table
+                .filter(archetype_version_assets::archetype_id.eq(id))
+                .filter(archetype_version_assets::archetype_version.eq(archetype_version.version))
+                .filter(archetype_version_assets::name.eq(&name));
+            delete(existing).execute(conn)?;
+            if let Some(hash) = asset {
+                insert_into(archetype_version_assets::table)
+                    .values((
+                        archetype_version_assets::archetype_id.eq(id),
+                        archetype_version_assets::archetype_version.eq(archetype_version.version),
+                        archetype_version_assets::name.eq(&name),
+                        archetype_version_assets::asset_hash.eq(hash),
+                    ))
+                    .execute(conn)?;
+            }
             Ok(archetype_version)
         })?;
 
@@ -112,4 +345,35 @@ impl Mutation {
         context.archetype_versions().prime(archetype_version);
         Ok(query)
     }
+
+    pub(super) fn set_archetype_editors(
+        &self,
+        context: &Context,
+        SetArchetypeEditors { id, editors }: SetArchetypeEditors,
+    ) -> anyhow::Result<query::Archetype> {
+        let account_id = context.try_authenticated_account()?;
+        let archetype = context.transaction(|conn| {
+            let archetype = archetypes::table
+                .filter(archetypes::id.eq(id))
+                .get_result::<Archetype>(conn)?;
+            self.assert_universe_maintainer(archetype.universe_id, account_id, conn)?;
+            delete(archetype_editors::table.filter(archetype_editors::archetype_id.eq(id)))
+                .execute(conn)?;
+            for editor in editors.unwrap_or_default() {
+                self.assert_universe_contributor(archetype.universe_id, editor, conn)?;
+                insert_into(archetype_editors::table)
+                    .values((
+                        archetype_editors::archetype_id.eq(id),
+                        archetype_editors::account_id.eq(editor),
+                    ))
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+            }
+            Ok(archetype)
+        })?;
+
+        let query = query::Archetype::new(archetype.id);
+        context.archetypes().prime(archetype);
+        Ok(query)
+    }
 }
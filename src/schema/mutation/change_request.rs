@@ -0,0 +1,415 @@
+use super::{ChangeRequest, ChangeRequestComment, Context, Mutation};
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ProposedScript {
+    id: Uuid,
+    script: String,
+    revision: Option<String>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct CreateChangeRequest {
+    universe: Uuid,
+    title: String,
+    description: Option<String>,
+    archetypes: Option<Vec<ProposedScript>>,
+    maps: Option<Vec<ProposedScript>>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct UpdateChangeRequest {
+    id: Uuid,
+    title: Option<String>,
+    description: Option<String>,
+    archetypes: Option<Vec<ProposedScript>>,
+    maps: Option<Vec<ProposedScript>>,
+    remove_archetypes: Option<Vec<Uuid>>,
+    remove_maps: Option<Vec<Uuid>>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct CommentOnChangeRequest {
+    id: Uuid,
+    body: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ReviewChangeRequest {
+    id: Uuid,
+}
+
+impl Mutation {
+    pub(super) fn create_change_request(
+        &self,
+        context: &Context,
+        CreateChangeRequest {
+            universe,
+            title,
+            description,
+            archetypes,
+            maps,
+        }: CreateChangeRequest,
+    ) -> anyhow::Result<ChangeRequest> {
+        let account_id = context.try_authenticated_account()?;
+        let change_request = context.transaction(|conn| {
+            self.assert_universe_contributor(universe, account_id, conn)?;
+            let change_request: data::ChangeRequest = insert_into(change_requests::table)
+                .values((
+                    change_requests::universe_id.eq(universe),
+                    change_requests::author_id.eq(account_id),
+                    change_requests::title.eq(&title),
+                    change_requests::description.eq(&description),
+                    change_requests::state.eq(ChangeRequestState::Open),
+                ))
+                .returning(change_requests::all_columns)
+                .get_result(conn)?;
+            self.propose_scripts(
+                &change_request,
+                archetypes.unwrap_or_default(),
+                maps.unwrap_or_default(),
+                conn,
+            )?;
+            Ok(change_request)
+        })?;
+
+        let query = ChangeRequest::new(change_request.id);
+        context.change_requests().prime(change_request);
+        Ok(query)
+    }
+
+    pub(super) fn update_change_request(
+        &self,
+        context: &Context,
+        UpdateChangeRequest {
+            id,
+            title,
+            description,
+            archetypes,
+            maps,
+            remove_archetypes,
+            remove_maps,
+        }: UpdateChangeRequest,
+    ) -> anyhow::Result<ChangeRequest> {
+        let account_id = context.try_authenticated_account()?;
+        let change_request = context.transaction(|conn| {
+            let change_request = self.open_change_request(id, conn)?;
+            anyhow::ensure!(
+                change_request.author_id == account_id,
+                "Only the author of this change request ({}) may update it",
+                id,
+            );
+            self.assert_universe_contributor(change_request.universe_id, account_id, conn)?;
+            if let Some(title) = title {
+                update(&change_request)
+                    .set(change_requests::title.eq(title))
+                    .execute(conn)?;
+            }
+            if let Some(description) = description {
+                update(&change_request)
+                    .set(change_requests::description.eq(description))
+                    .execute(conn)?;
+            }
+
+            let changes_scripts = archetypes.is_some()
+                || maps.is_some()
+                || remove_archetypes.is_some()
+                || remove_maps.is_some();
+            self.propose_scripts(
+                &change_request,
+                archetypes.unwrap_or_default(),
+                maps.unwrap_or_default(),
+                conn,
+            )?;
+            if let Some(remove_archetypes) = remove_archetypes {
+                let to_delete = change_request_archetypes::table
+                    .filter(change_request_archetypes::change_request_id.eq(id))
+                    .filter(change_request_archetypes::archetype_id.eq_any(remove_archetypes));
+                delete(to_delete).execute(conn)?;
+            }
+            if let Some(remove_maps) = remove_maps {
+                let to_delete = change_request_maps::table
+                    .filter(change_request_maps::change_request_id.eq(id))
+                    .filter(change_request_maps::map_id.eq_any(remove_maps));
+                delete(to_delete).execute(conn)?;
+            }
+            if changes_scripts {
+                // Approvals were given for the previous contents, so must be given again.
+                let approvals = change_request_approvals::table
+                    .filter(change_request_approvals::change_request_id.eq(id));
+                delete(approvals).execute(conn)?;
+            }
+
+            let change_request = update(&change_request)
+                .set(change_requests::updated_at.eq(now))
+                .returning(change_requests::all_columns)
+                .get_result(conn)?;
+            Ok(change_request)
+        })?;
+
+        let query = ChangeRequest::new(change_request.id);
+        context.change_requests().prime(change_request);
+        Ok(query)
+    }
+
+    pub(super) fn comment_on_change_request(
+        &self,
+        context: &Context,
+        CommentOnChangeRequest { id, body }: CommentOnChangeRequest,
+    ) -> anyhow::Result<ChangeRequestComment> {
+        let account_id = context.try_authenticated_account()?;
+        let comment = context.transaction(|conn| {
+            let change_request: data::ChangeRequest =
+                change_requests::table.find(id).get_result(conn)?;
+            self.assert_universe_reviewer(change_request.universe_id, account_id, conn)?;
+            let comment: data::ChangeRequestComment = insert_into(change_request_comments::table)
+                .values((
+                    change_request_comments::change_request_id.eq(id),
+                    change_request_comments::account_id.eq(account_id),
+                    change_request_comments::body.eq(&body),
+                ))
+                .returning(change_request_comments::all_columns)
+                .get_result(conn)?;
+            Ok(comment)
+        })?;
+
+        let query = ChangeRequestComment::new(comment.id);
+        context.change_request_comments().prime(comment);
+        Ok(query)
+    }
+
+    pub(super) fn approve_change_request(
+        &self,
+        context: &Context,
+        ReviewChangeRequest { id }: ReviewChangeRequest,
+    ) -> anyhow::Result<ChangeRequest> {
+        let account_id = context.try_authenticated_account()?;
+        let change_request = context.transaction(|conn| {
+            let change_request = self.open_change_request(id, conn)?;
+            self.assert_universe_reviewer(change_request.universe_id, account_id, conn)?;
+            anyhow::ensure!(
+                change_request.author_id != account_id,
+                "You cannot approve your own change request ({})",
+                id,
+            );
+            insert_into(change_request_approvals::table)
+                .values((
+                    change_request_approvals::change_request_id.eq(id),
+                    change_request_approvals::account_id.eq(account_id),
+                ))
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+            Ok(change_request)
+        })?;
+
+        let query = ChangeRequest::new(change_request.id);
+        context.change_requests().prime(change_request);
+        Ok(query)
+    }
+
+    pub(super) fn merge_change_request(
+        &self,
+        context: &Context,
+        ReviewChangeRequest { id }: ReviewChangeRequest,
+    ) -> anyhow::Result<ChangeRequest> {
+        let account_id = context.try_authenticated_account()?;
+        let change_request = context.transaction(|conn| {
+            let change_request = self.open_change_request(id, conn)?;
+            self.assert_universe_maintainer(change_request.universe_id, account_id, conn)?;
+            // Approvals only count while whoever gave them is still able to review.
+            let approvals = change_request_approvals::table
+                .inner_join(
+                    contributors::table
+                        .on(contributors::account_id.eq(change_request_approvals::account_id)),
+                )
+                .filter(change_request_approvals::change_request_id.eq(id))
+                .filter(contributors::universe_id.eq(change_request.universe_id))
+                .filter(contributors::role.eq_any(vec![
+                    ContributorRole::Owner,
+                    ContributorRole::Maintainer,
+                    ContributorRole::Contributor,
+                    ContributorRole::Reviewer,
+                ]));
+            let is_approved: bool = select(exists(approvals)).get_result(conn)?;
+            anyhow::ensure!(
+                is_approved,
+                "This change request ({}) must be approved before it can be merged",
+                id,
+            );
+
+            // Each proposed script must still be based on the current revision, so that merging
+            // does not overwrite changes made since the change request was proposed.
+            let mut conflicted = false;
+            let proposed_archetypes: Vec<(ChangeRequestArchetype, Archetype)> =
+                change_request_archetypes::table
+                    .inner_join(archetypes::table)
+                    .filter(change_request_archetypes::change_request_id.eq(id))
+                    .for_update()
+                    .load(conn)?;
+            for (proposed, _) in &proposed_archetypes {
+                conflicted |=
+                    self.archetype_revision(proposed.archetype_id, conn)? != proposed.base_revision;
+            }
+            let proposed_maps: Vec<(ChangeRequestMap, Map)> = change_request_maps::table
+                .inner_join(maps::table)
+                .filter(change_request_maps::change_request_id.eq(id))
+                .for_update()
+                .load(conn)?;
+            for (proposed, _) in &proposed_maps {
+                conflicted |= self.map_revision(proposed.map_id, conn)? != proposed.base_revision;
+            }
+            anyhow::ensure!(
+                !conflicted,
+                "This change request ({}) conflicts with changes made since it was proposed. Its conflicted scripts must be updated before it can be merged",
+                id,
+            );
+
+            for (proposed, archetype) in proposed_archetypes {
+                self.write_archetype_script(&archetype, &proposed.script, conn)?;
+            }
+            for (proposed, map) in proposed_maps {
+                self.write_map_script(&map, &proposed.script, conn)?;
+            }
+
+            let change_request = update(&change_request)
+                .set((
+                    change_requests::state.eq(ChangeRequestState::Merged),
+                    change_requests::merged_by.eq(account_id),
+                    change_requests::closed_at.eq(now),
+                    change_requests::updated_at.eq(now),
+                ))
+                .returning(change_requests::all_columns)
+                .get_result(conn)?;
+            Ok(change_request)
+        })?;
+
+        let query = ChangeRequest::new(change_request.id);
+        context.change_requests().prime(change_request);
+        Ok(query)
+    }
+
+    pub(super) fn close_change_request(
+        &self,
+        context: &Context,
+        ReviewChangeRequest { id }: ReviewChangeRequest,
+    ) -> anyhow::Result<ChangeRequest> {
+        let account_id = context.try_authenticated_account()?;
+        let change_request = context.transaction(|conn| {
+            let change_request = self.open_change_request(id, conn)?;
+            if change_request.author_id != account_id {
+                self.assert_universe_maintainer(change_request.universe_id, account_id, conn)?;
+            }
+            let change_request = update(&change_request)
+                .set((
+                    change_requests::state.eq(ChangeRequestState::Closed),
+                    change_requests::closed_at.eq(now),
+                    change_requests::updated_at.eq(now),
+                ))
+                .returning(change_requests::all_columns)
+                .get_result(conn)?;
+            Ok(change_request)
+        })?;
+
+        let query = ChangeRequest::new(change_request.id);
+        context.change_requests().prime(change_request);
+        Ok(query)
+    }
+
+    fn open_change_request(
+        &self,
+        id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<data::ChangeRequest> {
+        let change_request: data::ChangeRequest =
+            change_requests::table.find(id).get_result(conn)?;
+        anyhow::ensure!(
+            change_request.state == ChangeRequestState::Open,
+            "This change request ({}) is no longer open",
+            id,
+        );
+        Ok(change_request)
+    }
+
+    fn propose_scripts(
+        &self,
+        change_request: &data::ChangeRequest,
+        archetypes: Vec<ProposedScript>,
+        maps: Vec<ProposedScript>,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        for ProposedScript {
+            id,
+            script,
+            revision,
+        } in archetypes
+        {
+            let archetype: Archetype = archetypes::table.find(id).get_result(conn)?;
+            anyhow::ensure!(
+                archetype.universe_id == change_request.universe_id,
+                "This archetype ({}) does not belong to this universe ({})",
+                id,
+                change_request.universe_id,
+            );
+            let base_revision = match revision {
+                Some(revision) => revision,
+                None => self.archetype_revision(id, conn)?,
+            };
+            insert_into(change_request_archetypes::table)
+                .values((
+                    change_request_archetypes::change_request_id.eq(change_request.id),
+                    change_request_archetypes::archetype_id.eq(id),
+                    change_request_archetypes::script.eq(&script),
+                    change_request_archetypes::base_revision.eq(&base_revision),
+                ))
+                .on_conflict((
+                    change_request_archetypes::change_request_id,
+                    change_request_archetypes::archetype_id,
+                ))
+                .do_update()
+                .set((
+                    change_request_archetypes::script.eq(&script),
+                    change_request_archetypes::base_revision.eq(&base_revision),
+                ))
+                .execute(conn)?;
+        }
+        for ProposedScript {
+            id,
+            script,
+            revision,
+        } in maps
+        {
+            let map: Map = maps::table.find(id).get_result(conn)?;
+            anyhow::ensure!(
+                map.universe_id == change_request.universe_id,
+                "This map ({}) does not belong to this universe ({})",
+                id,
+                change_request.universe_id,
+            );
+            let base_revision = match revision {
+                Some(revision) => revision,
+                None => self.map_revision(id, conn)?,
+            };
+            insert_into(change_request_maps::table)
+                .values((
+                    change_request_maps::change_request_id.eq(change_request.id),
+                    change_request_maps::map_id.eq(id),
+                    change_request_maps::script.eq(&script),
+                    change_request_maps::base_revision.eq(&base_revision),
+                ))
+                .on_conflict((
+                    change_request_maps::change_request_id,
+                    change_request_maps::map_id,
+                ))
+                .do_update()
+                .set((
+                    change_request_maps::script.eq(&script),
+                    change_request_maps::base_revision.eq(&base_revision),
+                ))
+                .execute(conn)?;
+        }
+        Ok(())
+    }
+}
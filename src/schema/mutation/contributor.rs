@@ -1,5 +1,5 @@
 use super::{Context, Contributor, Mutation};
-use data::{contributors, ContributorRole};
+use data::{contributors, ContributorRole, NotificationKind};
 use diesel::dsl::*;
 use diesel::prelude::*;
 use uuid::Uuid;
@@ -15,6 +15,36 @@ pub struct ContributorInvitation {
     universe_id: Uuid,
 }
 
+#[derive(juniper::GraphQLInputObject)]
+pub struct CancelContributorInvitation {
+    account_id: Uuid,
+    universe_id: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RemoveContributor {
+    account_id: Uuid,
+    universe_id: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct LeaveUniverse {
+    universe_id: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct SetContributorRole {
+    account_id: Uuid,
+    universe_id: Uuid,
+    role: ContributorRole,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct TransferUniverseOwnership {
+    account_id: Uuid,
+    universe_id: Uuid,
+}
+
 impl Mutation {
     pub(super) fn invite_contributor(
         &self,
@@ -24,6 +54,7 @@ impl Mutation {
         let account_id = context.try_authenticated_account()?;
         let invitation = context.transaction(|conn| {
             self.assert_universe_owner(contributor.universe_id, account_id, conn)?;
+            self.assert_not_blocked(account_id, contributor.account_id, conn)?;
             let existing_contributor = contributors::table
                 .filter(contributors::account_id.eq(contributor.account_id))
                 .filter(contributors::universe_id.eq(contributor.universe_id))
@@ -44,6 +75,20 @@ impl Mutation {
                 ))
                 .returning(contributors::all_columns)
                 .get_result(conn)?;
+            self.record_contributor_change(
+                invitation.universe_id,
+                invitation.account_id,
+                account_id,
+                Some(invitation.role),
+                conn,
+            )?;
+            self.notify(
+                &[invitation.account_id],
+                NotificationKind::ContributorInvitation,
+                None,
+                Some(invitation.universe_id),
+                conn,
+            )?;
             Ok(invitation)
         })?;
         let query = Contributor::new(invitation.universe_id, invitation.account_id);
@@ -79,6 +124,184 @@ impl Mutation {
             update(&contributor)
                 .set(contributors::role.eq(contributor.role))
                 .execute(conn)?;
+            self.record_contributor_change(
+                universe_id,
+                account_id,
+                account_id,
+                Some(contributor.role),
+                conn,
+            )?;
+            Ok(contributor)
+        })?;
+        let query = Contributor::new(contributor.universe_id, contributor.account_id);
+        context.contributors().prime(contributor);
+        Ok(query)
+    }
+
+    pub(super) fn cancel_contributor_invitation(
+        &self,
+        context: &Context,
+        CancelContributorInvitation {
+            account_id: invitee_id,
+            universe_id,
+        }: CancelContributorInvitation,
+    ) -> anyhow::Result<()> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            self.assert_universe_owner(universe_id, account_id, conn)?;
+            let invitation = self.load_contributor(universe_id, invitee_id, conn)?;
+            anyhow::ensure!(
+                invitation.role == ContributorRole::Pending,
+                "That account ({}) does not have a pending invitation to this universe ({})",
+                invitee_id,
+                universe_id,
+            );
+            delete(&invitation).execute(conn)?;
+            self.record_contributor_change(universe_id, invitee_id, account_id, None, conn)?;
+            Ok(())
+        })
+    }
+
+    pub(super) fn remove_contributor(
+        &self,
+        context: &Context,
+        RemoveContributor {
+            account_id: contributor_id,
+            universe_id,
+        }: RemoveContributor,
+    ) -> anyhow::Result<()> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            self.assert_universe_owner(universe_id, account_id, conn)?;
+            let contributor = self.load_contributor(universe_id, contributor_id, conn)?;
+            anyhow::ensure!(
+                contributor.role != ContributorRole::Owner,
+                "The owner of a universe cannot be removed. Transfer ownership first.",
+            );
+            anyhow::ensure!(
+                contributor.role != ContributorRole::Pending,
+                "That account ({}) has not yet accepted their invitation. Cancel the invitation instead.",
+                contributor_id,
+            );
+            delete(&contributor).execute(conn)?;
+            self.record_contributor_change(universe_id, contributor_id, account_id, None, conn)?;
+            Ok(())
+        })
+    }
+
+    pub(super) fn leave_universe(
+        &self,
+        context: &Context,
+        LeaveUniverse { universe_id }: LeaveUniverse,
+    ) -> anyhow::Result<()> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            let contributor = self.load_contributor(universe_id, account_id, conn)?;
+            anyhow::ensure!(
+                contributor.role != ContributorRole::Owner,
+                "The owner of a universe cannot leave it. Transfer ownership first.",
+            );
+            delete(&contributor).execute(conn)?;
+            self.record_contributor_change(universe_id, account_id, account_id, None, conn)?;
+            Ok(())
+        })
+    }
+
+    pub(super) fn transfer_universe_ownership(
+        &self,
+        context: &Context,
+        TransferUniverseOwnership {
+            account_id: new_owner_id,
+            universe_id,
+        }: TransferUniverseOwnership,
+    ) -> anyhow::Result<Contributor> {
+        let account_id = context.try_authenticated_account()?;
+        let (owner, new_owner) = context.transaction(|conn| {
+            self.assert_universe_owner(universe_id, account_id, conn)?;
+            let mut new_owner = self.load_contributor(universe_id, new_owner_id, conn)?;
+            anyhow::ensure!(
+                new_owner.role == ContributorRole::Maintainer
+                    || new_owner.role == ContributorRole::Contributor,
+                "Ownership can only be transferred to an active contributor of this universe ({})",
+                universe_id,
+            );
+            let mut owner = self.load_contributor(universe_id, account_id, conn)?;
+            owner.role = ContributorRole::Contributor;
+            update(&owner)
+                .set(contributors::role.eq(owner.role))
+                .execute(conn)?;
+            self.record_contributor_change(
+                universe_id,
+                account_id,
+                account_id,
+                Some(owner.role),
+                conn,
+            )?;
+            new_owner.role = ContributorRole::Owner;
+            update(&new_owner)
+                .set(contributors::role.eq(new_owner.role))
+                .execute(conn)?;
+            self.record_contributor_change(
+                universe_id,
+                new_owner_id,
+                account_id,
+                Some(new_owner.role),
+                conn,
+            )?;
+            Ok((owner, new_owner))
+        })?;
+        let query = Contributor::new(new_owner.universe_id, new_owner.account_id);
+        context.contributors().prime(owner);
+        context.contributors().prime(new_owner);
+        Ok(query)
+    }
+
+    pub(super) fn set_contributor_role(
+        &self,
+        context: &Context,
+        SetContributorRole {
+            account_id: contributor_id,
+            universe_id,
+            role,
+        }: SetContributorRole,
+    ) -> anyhow::Result<Contributor> {
+        let account_id = context.try_authenticated_account()?;
+        let contributor = context.transaction(|conn| {
+            self.assert_universe_owner(universe_id, account_id, conn)?;
+            anyhow::ensure!(
+                [
+                    ContributorRole::Maintainer,
+                    ContributorRole::Contributor,
+                    ContributorRole::Reviewer,
+                    ContributorRole::Viewer,
+                ]
+                .contains(&role),
+                "A contributor cannot be given this role ({:?})",
+                role,
+            );
+            let mut contributor = self.load_contributor(universe_id, contributor_id, conn)?;
+            anyhow::ensure!(
+                ![
+                    ContributorRole::Owner,
+                    ContributorRole::Pending,
+                    ContributorRole::Declined,
+                ]
+                .contains(&contributor.role),
+                "The role of this account ({}) in this universe ({}) cannot be changed",
+                contributor_id,
+                universe_id,
+            );
+            contributor.role = role;
+            update(&contributor)
+                .set(contributors::role.eq(contributor.role))
+                .execute(conn)?;
+            self.record_contributor_change(
+                universe_id,
+                contributor_id,
+                account_id,
+                Some(contributor.role),
+                conn,
+            )?;
             Ok(contributor)
         })?;
         let query = Contributor::new(contributor.universe_id, contributor.account_id);
@@ -0,0 +1,162 @@
+use super::{Context, Mutation};
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct SendFriendRequest {
+    account: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct FriendRequest {
+    account: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RemoveFriend {
+    account: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct BlockAccount {
+    account: Uuid,
+}
+
+impl Mutation {
+    pub(super) fn send_friend_request(
+        &self,
+        context: &Context,
+        SendFriendRequest { account }: SendFriendRequest,
+    ) -> anyhow::Result<()> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            anyhow::ensure!(
+                account != account_id,
+                "You cannot send a friend request to yourself",
+            );
+            let account_exists =
+                select(exists(accounts::table.find(account))).get_result::<bool>(conn)?;
+            anyhow::ensure!(
+                account_exists,
+                "That account ({}) could not be found",
+                account,
+            );
+            self.assert_not_blocked(account_id, account, conn)?;
+            anyhow::ensure!(
+                self.load_friendship(account_id, account, conn)?.is_none(),
+                "You are already friends with, or have a friend request pending with, that account ({})",
+                account,
+            );
+            insert_into(friendships::table)
+                .values((
+                    friendships::requester_id.eq(account_id),
+                    friendships::addressee_id.eq(account),
+                    friendships::state.eq(FriendshipState::Pending),
+                ))
+                .execute(conn)?;
+            self.notify(
+                &[account],
+                NotificationKind::FriendRequest,
+                None,
+                None,
+                conn,
+            )?;
+            Ok(())
+        })
+    }
+
+    pub(super) fn respond_to_friend_request(
+        &self,
+        context: &Context,
+        FriendRequest { account }: FriendRequest,
+        accepted: bool,
+    ) -> anyhow::Result<()> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            let request = friendships::table
+                .find((account, account_id))
+                .filter(friendships::state.eq(FriendshipState::Pending));
+            if accepted {
+                let updated = update(request)
+                    .set((
+                        friendships::state.eq(FriendshipState::Accepted),
+                        friendships::accepted_at.eq(now),
+                    ))
+                    .execute(conn)?;
+                anyhow::ensure!(
+                    updated == 1,
+                    "That account ({}) has not sent you a friend request",
+                    account,
+                );
+            } else {
+                let deleted = delete(request).execute(conn)?;
+                anyhow::ensure!(
+                    deleted == 1,
+                    "That account ({}) has not sent you a friend request",
+                    account,
+                );
+            }
+            Ok(())
+        })
+    }
+
+    pub(super) fn remove_friend(
+        &self,
+        context: &Context,
+        RemoveFriend { account }: RemoveFriend,
+    ) -> anyhow::Result<()> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            let friendship = self
+                .load_friendship(account_id, account, conn)?
+                .ok_or_else(|| {
+                    anyhow::anyhow!("You are not friends with that account ({})", account)
+                })?;
+            delete(&friendship).execute(conn)?;
+            Ok(())
+        })
+    }
+
+    pub(super) fn block_account(
+        &self,
+        context: &Context,
+        BlockAccount { account }: BlockAccount,
+    ) -> anyhow::Result<()> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            anyhow::ensure!(account != account_id, "You cannot block yourself");
+            let account_exists =
+                select(exists(accounts::table.find(account))).get_result::<bool>(conn)?;
+            anyhow::ensure!(
+                account_exists,
+                "That account ({}) could not be found",
+                account,
+            );
+            if let Some(friendship) = self.load_friendship(account_id, account, conn)? {
+                delete(&friendship).execute(conn)?;
+            }
+            insert_into(blocks::table)
+                .values((
+                    blocks::account_id.eq(account_id),
+                    blocks::blocked_id.eq(account),
+                ))
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    pub(super) fn unblock_account(
+        &self,
+        context: &Context,
+        BlockAccount { account }: BlockAccount,
+    ) -> anyhow::Result<()> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            delete(blocks::table.find((account_id, account))).execute(conn)?;
+            Ok(())
+        })
+    }
+}
@@ -11,6 +11,17 @@ pub struct CreateGame {
     map: Uuid,
     seed: String,
     players: Vec<Uuid>,
+    open_seats: Option<i32>,
+    time_control: Option<TimeControl>,
+    on_departure: Option<DepartureRule>,
+}
+
+#[derive(Default, juniper::GraphQLInputObject)]
+pub struct TimeControl {
+    turn_limit: Option<i32>,
+    time_bank: Option<i32>,
+    on_timeout: Option<TimeoutAction>,
+    forfeit_after: Option<i32>,
 }
 
 #[derive(juniper::GraphQLInputObject)]
@@ -18,6 +29,45 @@ pub struct GameInvitation {
     id: Uuid,
 }
 
+#[derive(juniper::GraphQLInputObject)]
+pub struct JoinGame {
+    id: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct LeaveLobby {
+    id: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct InviteToGame {
+    game: Uuid,
+    account: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ReplacePlayer {
+    game: Uuid,
+    account: Uuid,
+    replacement: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct EndTurn {
+    id: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ResignGame {
+    id: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RemovePlayer {
+    game: Uuid,
+    account: Uuid,
+}
+
 impl Mutation {
     pub(super) fn create_game(
         &self,
@@ -28,6 +78,9 @@ impl Mutation {
             map,
             seed,
             players,
+            open_seats,
+            time_control,
+            on_departure,
         }: CreateGame,
     ) -> anyhow::Result<Game> {
         let account_id = context.try_authenticated_account()?;
@@ -37,15 +90,52 @@ impl Mutation {
             players.contains(&account_id),
             "You cannot create a game where you are not one of the players",
         );
+        let open_seats = open_seats.unwrap_or(0);
+        anyhow::ensure!(
+            open_seats >= 0,
+            "A game cannot have a negative number of open seats",
+        );
+        let seats = players.len() as i32 + open_seats;
+        let TimeControl {
+            turn_limit,
+            time_bank,
+            on_timeout,
+            forfeit_after,
+        } = time_control.unwrap_or_default();
+        anyhow::ensure!(
+            turn_limit.unwrap_or(1) > 0 && time_bank.unwrap_or(0) >= 0,
+            "A turn limit must be positive, and a time bank cannot be negative",
+        );
+        // Without a turn limit, the time bank is all a player has, so an empty one would time
+        // out every turn as soon as it started.
+        anyhow::ensure!(
+            turn_limit.is_some() || time_bank.map(|time_bank| time_bank > 0).unwrap_or(true),
+            "A time bank must be positive when there is no turn limit",
+        );
+        anyhow::ensure!(
+            forfeit_after.unwrap_or(1) > 0,
+            "Players must be allowed at least one timeout before they forfeit",
+        );
         let game = context.transaction(|conn| {
+            self.assert_universe_visible(universe, account_id, conn)?;
+            for player in &players {
+                self.assert_not_blocked(account_id, *player, conn)?;
+                anyhow::ensure!(
+                    self.universe_visible_to(universe, *player, conn)?,
+                    "A player you have invited ({}) is not able to see this universe ({})",
+                    player,
+                    universe,
+                );
+            }
             let universe_version = universe_versions::table
                 .select(max(universe_versions::version))
                 .filter(universe_versions::universe_id.eq(universe))
                 .filter(universe_versions::released_at.is_not_null())
+                .filter(universe_versions::yanked_at.is_null())
                 .get_result::<Option<i32>>(conn)?
                 .ok_or_else(|| {
                     anyhow::anyhow!(
-                        "This universe ({}) does not exist, or has not been released",
+                        "This universe ({}) does not exist, or has no released version available",
                         universe
                     )
                 })?;
@@ -69,9 +159,16 @@ impl Mutation {
                     games::universe_version.eq(universe_version),
                     games::map_id.eq(map),
                     games::map_seed.eq(seed),
+                    games::seats.eq(seats),
+                    games::turn_limit.eq(turn_limit),
+                    games::time_bank.eq(time_bank),
+                    games::timeout_action.eq(on_timeout.unwrap_or(TimeoutAction::SkipTurn)),
+                    games::forfeit_after.eq(forfeit_after),
+                    games::departure_rule.eq(on_departure.unwrap_or(DepartureRule::Neutralize)),
                 ))
                 .returning(games::all_columns)
                 .get_result(conn)?;
+            self.assert_seats_within_map(&game, seats, conn)?;
 
             for (i, player) in players.into_iter().enumerate() {
                 let player_exists =
@@ -94,7 +191,19 @@ impl Mutation {
                         players::engagement.eq(engagement),
                     ))
                     .execute(conn)?;
+                if engagement == PlayerEngagement::Pending {
+                    self.notify(
+                        &[player],
+                        NotificationKind::GameInvitation,
+                        Some(game.id),
+                        Some(universe),
+                        conn,
+                    )?;
+                }
             }
+            self.start_game_if_full(game.id, conn)?;
+
+            let game: data::Game = games::table.find(game.id).get_result(conn)?;
             Ok(game)
         })?;
 
@@ -127,6 +236,267 @@ impl Mutation {
             update(&player)
                 .set(players::engagement.eq(engagement))
                 .execute(conn)?;
+            if !accepted {
+                // The declined seat is removed rather than opened to the public, so the game can
+                // still start once the remaining players accept.
+                update(games::table.find(id))
+                    .filter(games::started_at.is_null())
+                    .set(games::seats.eq(games::seats - 1))
+                    .execute(conn)?;
+            }
+            self.start_game_if_full(id, conn)?;
+
+            let game: data::Game = games::table.find(id).get_result(conn)?;
+            Ok(game)
+        })?;
+
+        let query = Game::new(game.id);
+        context.games().prime(game);
+        Ok(query)
+    }
+
+    pub(super) fn join_game(
+        &self,
+        context: &Context,
+        JoinGame { id }: JoinGame,
+    ) -> anyhow::Result<Game> {
+        let account_id = context.try_authenticated_account()?;
+        let game = context.transaction(|conn| {
+            let game: data::Game = games::table.find(id).for_update().get_result(conn)?;
+            anyhow::ensure!(
+                game.started_at.is_none(),
+                "This game ({}) has already started",
+                id,
+            );
+            self.assert_universe_visible(game.universe_id, account_id, conn)?;
+            let player: Option<data::Player> = players::table
+                .filter(players::game_id.eq(id))
+                .filter(players::account_id.eq(account_id))
+                .get_result(conn)
+                .optional()?;
+            match player {
+                Some(player) if player.engagement == PlayerEngagement::Pending => {
+                    update(&player)
+                        .set(players::engagement.eq(PlayerEngagement::Player))
+                        .execute(conn)?;
+                }
+                Some(player) if player.engagement == PlayerEngagement::Declined => {
+                    anyhow::ensure!(
+                        self.game_open_seats(&game, conn)? > 0,
+                        "This game ({}) has no open seats",
+                        id,
+                    );
+                    update(&player)
+                        .set(players::engagement.eq(PlayerEngagement::Player))
+                        .execute(conn)?;
+                }
+                Some(..) => anyhow::bail!("You are already in this game ({})", id),
+                None => {
+                    anyhow::ensure!(
+                        self.game_open_seats(&game, conn)? > 0,
+                        "This game ({}) has no open seats",
+                        id,
+                    );
+                    let last_turn: Option<i32> = players::table
+                        .select(max(players::turn_order))
+                        .filter(players::game_id.eq(id))
+                        .get_result(conn)?;
+                    insert_into(players::table)
+                        .values((
+                            players::game_id.eq(id),
+                            players::account_id.eq(account_id),
+                            players::turn_order.eq(last_turn.map(|turn| turn + 1).unwrap_or(0)),
+                            players::engagement.eq(PlayerEngagement::Player),
+                        ))
+                        .execute(conn)?;
+                }
+            }
+            self.start_game_if_full(id, conn)?;
+
+            let game: data::Game = games::table.find(id).get_result(conn)?;
+            Ok(game)
+        })?;
+
+        let query = Game::new(game.id);
+        context.games().prime(game);
+        Ok(query)
+    }
+
+    pub(super) fn leave_lobby(
+        &self,
+        context: &Context,
+        LeaveLobby { id }: LeaveLobby,
+    ) -> anyhow::Result<Game> {
+        let account_id = context.try_authenticated_account()?;
+        let game = context.transaction(|conn| {
+            let game: data::Game = games::table.find(id).for_update().get_result(conn)?;
+            anyhow::ensure!(
+                game.started_at.is_none(),
+                "This game ({}) has already started",
+                id,
+            );
+            let player: data::Player = players::table
+                .filter(players::game_id.eq(id))
+                .filter(players::account_id.eq(account_id))
+                .get_result(conn)?;
+            anyhow::ensure!(
+                player.engagement != PlayerEngagement::Host,
+                "The host cannot leave the lobby of their own game",
+            );
+            delete(&player).execute(conn)?;
+            Ok(game)
+        })?;
+
+        let query = Game::new(game.id);
+        context.games().prime(game);
+        Ok(query)
+    }
+
+    pub(super) fn invite_to_game(
+        &self,
+        context: &Context,
+        InviteToGame { game, account }: InviteToGame,
+    ) -> anyhow::Result<Game> {
+        let account_id = context.try_authenticated_account()?;
+        let game = context.transaction(|conn| {
+            let game = self.game_in_lobby(game, account_id, conn)?;
+            self.assert_not_blocked(account_id, account, conn)?;
+            self.assert_invitable(&game, account, conn)?;
+            self.assert_seats_within_map(&game, game.seats + 1, conn)?;
+            let last_turn: Option<i32> = players::table
+                .select(max(players::turn_order))
+                .filter(players::game_id.eq(game.id))
+                .get_result(conn)?;
+            insert_into(players::table)
+                .values((
+                    players::game_id.eq(game.id),
+                    players::account_id.eq(account),
+                    players::turn_order.eq(last_turn.map(|turn| turn + 1).unwrap_or(0)),
+                    players::engagement.eq(PlayerEngagement::Pending),
+                ))
+                .on_conflict((players::game_id, players::account_id))
+                .do_update()
+                .set((
+                    players::turn_order.eq(last_turn.map(|turn| turn + 1).unwrap_or(0)),
+                    players::engagement.eq(PlayerEngagement::Pending),
+                    // A new invitation, so it is given a full lifetime before it expires.
+                    players::created_at.eq(now),
+                ))
+                .execute(conn)?;
+            self.notify(
+                &[account],
+                NotificationKind::GameInvitation,
+                Some(game.id),
+                Some(game.universe_id),
+                conn,
+            )?;
+            let game: data::Game = update(&game)
+                .set(games::seats.eq(games::seats + 1))
+                .returning(games::all_columns)
+                .get_result(conn)?;
+            Ok(game)
+        })?;
+
+        let query = Game::new(game.id);
+        context.games().prime(game);
+        Ok(query)
+    }
+
+    pub(super) fn replace_player(
+        &self,
+        context: &Context,
+        ReplacePlayer {
+            game,
+            account,
+            replacement,
+        }: ReplacePlayer,
+    ) -> anyhow::Result<Game> {
+        let account_id = context.try_authenticated_account()?;
+        let game = context.transaction(|conn| {
+            let game = self.game_in_lobby(game, account_id, conn)?;
+            let player: data::Player = players::table
+                .filter(players::game_id.eq(game.id))
+                .filter(players::account_id.eq(account))
+                .get_result(conn)?;
+            anyhow::ensure!(
+                player.engagement == PlayerEngagement::Pending
+                    || player.engagement == PlayerEngagement::Declined,
+                "Only players who have not accepted their invitation can be replaced",
+            );
+            self.assert_not_blocked(account_id, replacement, conn)?;
+            self.assert_invitable(&game, replacement, conn)?;
+            delete(&player).execute(conn)?;
+            insert_into(players::table)
+                .values((
+                    players::game_id.eq(game.id),
+                    players::account_id.eq(replacement),
+                    players::turn_order.eq(player.turn_order),
+                    players::engagement.eq(PlayerEngagement::Pending),
+                ))
+                .on_conflict((players::game_id, players::account_id))
+                .do_update()
+                .set((
+                    players::turn_order.eq(player.turn_order),
+                    players::engagement.eq(PlayerEngagement::Pending),
+                    players::created_at.eq(now),
+                ))
+                .execute(conn)?;
+            self.notify(
+                &[replacement],
+                NotificationKind::GameInvitation,
+                Some(game.id),
+                Some(game.universe_id),
+                conn,
+            )?;
+            let game = if player.engagement == PlayerEngagement::Declined {
+                // The declined player's seat was removed, so it is restored for the replacement.
+                self.assert_seats_within_map(&game, game.seats + 1, conn)?;
+                update(&game)
+                    .set(games::seats.eq(games::seats + 1))
+                    .returning(games::all_columns)
+                    .get_result(conn)?
+            } else {
+                game
+            };
+            Ok(game)
+        })?;
+
+        let query = Game::new(game.id);
+        context.games().prime(game);
+        Ok(query)
+    }
+
+    pub(super) fn end_turn(
+        &self,
+        context: &Context,
+        EndTurn { id }: EndTurn,
+    ) -> anyhow::Result<Game> {
+        let account_id = context.try_authenticated_account()?;
+        let check = |conn: &DbConnection| -> anyhow::Result<data::Game> {
+            let game: data::Game = games::table.find(id).for_update().get_result(conn)?;
+            anyhow::ensure!(
+                game.ended_at.is_none(),
+                "This game ({}) has already ended",
+                id,
+            );
+            let player = self.current_player(&game, conn)?;
+            anyhow::ensure!(
+                player.account_id == account_id,
+                "It is not your turn in this game ({})",
+                id,
+            );
+            Ok(game)
+        };
+        // The map's scripts are run before the game is locked, and their results are only used
+        // if the game has not changed in the meantime.
+        let snapshot = context.transaction(|conn| {
+            let game = check(conn)?;
+            self.game_snapshot(game, conn)
+        })?;
+        let prepared = tokio::task::block_in_place(|| snapshot.prepare_turn())?;
+        let game = context.transaction(|conn| {
+            let game = check(conn)?;
+            self.end_current_turn(&game, &prepared, conn)?;
 
             let game: data::Game = games::table.find(id).get_result(conn)?;
             Ok(game)
@@ -136,4 +506,153 @@ impl Mutation {
         context.games().prime(game);
         Ok(query)
     }
+
+    pub(super) fn resign_game(
+        &self,
+        context: &Context,
+        ResignGame { id }: ResignGame,
+    ) -> anyhow::Result<Game> {
+        let account_id = context.try_authenticated_account()?;
+        let check = |conn: &DbConnection| -> anyhow::Result<(data::Game, data::Player)> {
+            let game = self.game_in_progress(id, conn)?;
+            let player: data::Player = players::table
+                .filter(players::game_id.eq(id))
+                .filter(players::account_id.eq(account_id))
+                .get_result(conn)?;
+            anyhow::ensure!(
+                player.engagement == PlayerEngagement::Host
+                    || player.engagement == PlayerEngagement::Player,
+                "You ({}) are not playing in this game ({})",
+                account_id,
+                id,
+            );
+            Ok((game, player))
+        };
+        let snapshot = context.transaction(|conn| {
+            let (game, _) = check(conn)?;
+            self.game_snapshot(game, conn)
+        })?;
+        let prepared = tokio::task::block_in_place(|| snapshot.prepare_departure(account_id))?;
+        let game = context.transaction(|conn| {
+            let (game, player) = check(conn)?;
+            self.depart_game(&game, &player, PlayerEngagement::Resigned, &prepared, conn)?;
+
+            let game: data::Game = games::table.find(id).get_result(conn)?;
+            Ok(game)
+        })?;
+
+        let query = Game::new(game.id);
+        context.games().prime(game);
+        Ok(query)
+    }
+
+    pub(super) fn remove_player(
+        &self,
+        context: &Context,
+        RemovePlayer { game, account }: RemovePlayer,
+    ) -> anyhow::Result<Game> {
+        let account_id = context.try_authenticated_account()?;
+        let check = |conn: &DbConnection| -> anyhow::Result<(data::Game, data::Player)> {
+            let game = self.game_in_progress(game, conn)?;
+            self.assert_game_host(game.id, account_id, conn)?;
+            anyhow::ensure!(
+                account != account_id,
+                "You cannot remove yourself from a game. Resign instead.",
+            );
+            let player: data::Player = players::table
+                .filter(players::game_id.eq(game.id))
+                .filter(players::account_id.eq(account))
+                .get_result(conn)?;
+            anyhow::ensure!(
+                player.engagement == PlayerEngagement::Player,
+                "That account ({}) is not playing in this game ({})",
+                account,
+                game.id,
+            );
+            Ok((game, player))
+        };
+        let snapshot = context.transaction(|conn| {
+            let (game, _) = check(conn)?;
+            self.game_snapshot(game, conn)
+        })?;
+        let prepared = tokio::task::block_in_place(|| snapshot.prepare_departure(account))?;
+        let game = context.transaction(|conn| {
+            let (game, player) = check(conn)?;
+            self.depart_game(&game, &player, PlayerEngagement::Removed, &prepared, conn)?;
+
+            let game: data::Game = games::table.find(game.id).get_result(conn)?;
+            Ok(game)
+        })?;
+
+        let query = Game::new(game.id);
+        context.games().prime(game);
+        Ok(query)
+    }
+
+    fn game_in_progress(&self, id: Uuid, conn: &DbConnection) -> anyhow::Result<data::Game> {
+        let game: data::Game = games::table.find(id).for_update().get_result(conn)?;
+        anyhow::ensure!(
+            game.started_at.is_some(),
+            "This game ({}) has not yet started",
+            id,
+        );
+        anyhow::ensure!(
+            game.ended_at.is_none(),
+            "This game ({}) has already ended",
+            id,
+        );
+        Ok(game)
+    }
+
+    /// Loads a game which is still waiting for players, ensuring that it is hosted by this account.
+    fn game_in_lobby(
+        &self,
+        id: Uuid,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<data::Game> {
+        let game: data::Game = games::table.find(id).for_update().get_result(conn)?;
+        self.assert_game_host(id, account_id, conn)?;
+        anyhow::ensure!(
+            game.started_at.is_none(),
+            "This game ({}) has already started",
+            id,
+        );
+        Ok(game)
+    }
+
+    /// Ensures that an account may be invited to a game: it must exist, be able to see the game's
+    /// universe, and not already be in the game.
+    fn assert_invitable(
+        &self,
+        game: &data::Game,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let account_exists =
+            select(exists(accounts::table.find(account_id))).get_result::<bool>(conn)?;
+        anyhow::ensure!(
+            account_exists,
+            "The player you have invited ({}) could not be found",
+            account_id,
+        );
+        anyhow::ensure!(
+            self.universe_visible_to(game.universe_id, account_id, conn)?,
+            "The player you have invited ({}) is not able to see this universe ({})",
+            account_id,
+            game.universe_id,
+        );
+        let already_in_game = players::table
+            .filter(players::game_id.eq(game.id))
+            .filter(players::account_id.eq(account_id))
+            .filter(players::engagement.ne(PlayerEngagement::Declined));
+        let already_in_game: bool = select(exists(already_in_game)).get_result(conn)?;
+        anyhow::ensure!(
+            !already_in_game,
+            "That account ({}) is already in this game ({})",
+            account_id,
+            game.id,
+        );
+        Ok(())
+    }
 }
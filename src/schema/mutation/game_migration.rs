@@ -0,0 +1,416 @@
+use super::{Context, Game, Mutation};
+use crate::engine;
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ProposeGameMigration {
+    game: Uuid,
+    version: Option<i32>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct VoteOnGameMigration {
+    game: Uuid,
+    accept: bool,
+}
+
+/// A migration of a game to another version of its universe, prepared by running each
+/// archetype's migration hook on the game's entities. The hooks are run outside of any
+/// transaction, so the migration is only applied if the game has not changed since.
+struct PreparedMigration {
+    from_version: i32,
+    to_version: i32,
+    entities: Vec<(data::Entity, serde_json::Value)>,
+}
+
+impl PreparedMigration {
+    /// Whether this was prepared for the game as it is now, and for the migration it has now.
+    fn is_current(
+        &self,
+        game: &data::Game,
+        migration: &data::GameMigration,
+        game_entities: &[data::Entity],
+    ) -> bool {
+        self.from_version == game.universe_version
+            && self.to_version == migration.universe_version
+            && self.entities.len() == game_entities.len()
+            && self
+                .entities
+                .iter()
+                .zip(game_entities)
+                .all(|((before, _), entity)| {
+                    before.id == entity.id
+                        && before.archetype_id == entity.archetype_id
+                        && before.state == entity.state
+                })
+    }
+}
+
+impl Mutation {
+    pub(super) fn propose_game_migration(
+        &self,
+        context: &Context,
+        ProposeGameMigration { game, version }: ProposeGameMigration,
+    ) -> anyhow::Result<Game> {
+        let account_id = context.try_authenticated_account()?;
+        let (version, sole_player) = context.transaction(|conn| {
+            let game: data::Game = games::table.find(game).get_result(conn)?;
+            self.assert_game_host(game.id, account_id, conn)?;
+            let version = self.migration_target(&game, version, conn)?;
+            let sole_player = self.active_players(game.id, conn)? == [account_id];
+            Ok((version, sole_player))
+        })?;
+        // The proposal is accepted at once if nobody else is playing, so the migration is
+        // prepared before the game is locked.
+        let prepared = if sole_player {
+            Some(self.prepare_game_migration(context, game, version)?)
+        } else {
+            None
+        };
+        let game = context.transaction(|conn| {
+            let game: data::Game = games::table.find(game).get_result(conn)?;
+            self.assert_game_host(game.id, account_id, conn)?;
+            let version = self.migration_target(&game, Some(version), conn)?;
+
+            delete(game_migration_votes::table.filter(game_migration_votes::game_id.eq(game.id)))
+                .execute(conn)?;
+            insert_into(game_migrations::table)
+                .values((
+                    game_migrations::game_id.eq(game.id),
+                    game_migrations::universe_version.eq(version),
+                ))
+                .on_conflict(game_migrations::game_id)
+                .do_update()
+                .set((
+                    game_migrations::universe_version.eq(version),
+                    game_migrations::created_at.eq(now),
+                ))
+                .execute(conn)?;
+            insert_into(game_migration_votes::table)
+                .values((
+                    game_migration_votes::game_id.eq(game.id),
+                    game_migration_votes::account_id.eq(account_id),
+                    game_migration_votes::accepted.eq(true),
+                ))
+                .execute(conn)?;
+            self.migrate_game_if_accepted(game.id, prepared.as_ref(), conn)?;
+
+            let game: data::Game = games::table.find(game.id).get_result(conn)?;
+            Ok(game)
+        })?;
+
+        let query = Game::new(game.id);
+        context.games().prime(game);
+        Ok(query)
+    }
+
+    pub(super) fn vote_on_game_migration(
+        &self,
+        context: &Context,
+        VoteOnGameMigration { game, accept }: VoteOnGameMigration,
+    ) -> anyhow::Result<Game> {
+        let account_id = context.try_authenticated_account()?;
+        // If this vote is the last one the migration is waiting on, the migration is prepared
+        // before the game is locked.
+        let prepared = if accept {
+            let final_vote = context.transaction(|conn| {
+                let migration: Option<data::GameMigration> = game_migrations::table
+                    .find(game)
+                    .get_result(conn)
+                    .optional()?;
+                let awaiting = self.awaiting_migration_votes(game, conn)?;
+                Ok(migration.filter(|_| awaiting.iter().all(|player| *player == account_id)))
+            })?;
+            match final_vote {
+                Some(migration) => {
+                    Some(self.prepare_game_migration(context, game, migration.universe_version)?)
+                }
+                None => None,
+            }
+        } else {
+            None
+        };
+        let game = context.transaction(|conn| {
+            let player: data::Player = players::table
+                .filter(players::account_id.eq(account_id))
+                .filter(players::game_id.eq(game))
+                .get_result(conn)?;
+            anyhow::ensure!(
+                player.engagement == PlayerEngagement::Host
+                    || player.engagement == PlayerEngagement::Player,
+                "You ({}) are not playing in this game ({})",
+                account_id,
+                game,
+            );
+            let migration_exists =
+                select(exists(game_migrations::table.find(game))).get_result::<bool>(conn)?;
+            anyhow::ensure!(
+                migration_exists,
+                "There is no migration proposed for this game ({})",
+                game,
+            );
+
+            if accept {
+                insert_into(game_migration_votes::table)
+                    .values((
+                        game_migration_votes::game_id.eq(game),
+                        game_migration_votes::account_id.eq(account_id),
+                        game_migration_votes::accepted.eq(true),
+                    ))
+                    .on_conflict((
+                        game_migration_votes::game_id,
+                        game_migration_votes::account_id,
+                    ))
+                    .do_update()
+                    .set(game_migration_votes::accepted.eq(true))
+                    .execute(conn)?;
+                self.migrate_game_if_accepted(game, prepared.as_ref(), conn)?;
+            } else {
+                self.discard_game_migration(game, conn)?;
+            }
+
+            let game: data::Game = games::table.find(game).get_result(conn)?;
+            Ok(game)
+        })?;
+
+        let query = Game::new(game.id);
+        context.games().prime(game);
+        Ok(query)
+    }
+
+    /// Finds the version of its universe a game may be migrated to, defaulting to the latest
+    /// released version.
+    fn migration_target(
+        &self,
+        game: &data::Game,
+        version: Option<i32>,
+        conn: &DbConnection,
+    ) -> anyhow::Result<i32> {
+        let version = match version {
+            Some(version) => version,
+            None => universe_versions::table
+                .select(max(universe_versions::version))
+                .filter(universe_versions::universe_id.eq(game.universe_id))
+                .filter(universe_versions::released_at.is_not_null())
+                .filter(universe_versions::yanked_at.is_null())
+                .get_result::<Option<i32>>(conn)?
+                .unwrap_or(game.universe_version),
+        };
+        anyhow::ensure!(
+            version > game.universe_version,
+            "This game ({}) cannot be migrated from version {} to version {} of its universe",
+            game.id,
+            game.universe_version,
+            version,
+        );
+        let universe_version = self.released_universe_version(game.universe_id, version, conn)?;
+        anyhow::ensure!(
+            universe_version.yanked_at.is_none(),
+            "This version ({}) of the universe ({}) has been yanked",
+            version,
+            game.universe_id,
+        );
+        self.assert_game_migratable(game, version, conn)?;
+        Ok(version)
+    }
+
+    /// Ensures that everything a game currently uses still exists in a version of its universe.
+    fn assert_game_migratable(
+        &self,
+        game: &data::Game,
+        version: i32,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let map_exists = universe_version_maps::table
+            .filter(universe_version_maps::universe_id.eq(game.universe_id))
+            .filter(universe_version_maps::universe_version.eq(version))
+            .filter(universe_version_maps::map_id.eq(game.map_id));
+        let map_exists = select(exists(map_exists)).get_result::<bool>(conn)?;
+        anyhow::ensure!(
+            map_exists,
+            "This game's map ({}) is not available in version {} of the universe ({})",
+            game.map_id,
+            version,
+            game.universe_id,
+        );
+
+        let archetype_ids: Vec<Uuid> = entities::table
+            .select(entities::archetype_id)
+            .filter(entities::game_id.eq(game.id))
+            .distinct()
+            .load(conn)?;
+        for archetype_id in archetype_ids {
+            let archetype_exists = universe_version_archetypes::table
+                .filter(universe_version_archetypes::universe_id.eq(game.universe_id))
+                .filter(universe_version_archetypes::universe_version.eq(version))
+                .filter(universe_version_archetypes::archetype_id.eq(archetype_id));
+            let archetype_exists = select(exists(archetype_exists)).get_result::<bool>(conn)?;
+            anyhow::ensure!(
+                archetype_exists,
+                "An archetype ({}) used in this game is not available in version {} of the universe ({})",
+                archetype_id,
+                version,
+                game.universe_id,
+            );
+        }
+        Ok(())
+    }
+
+    fn discard_game_migration(&self, game_id: Uuid, conn: &DbConnection) -> anyhow::Result<()> {
+        delete(game_migration_votes::table.filter(game_migration_votes::game_id.eq(game_id)))
+            .execute(conn)?;
+        delete(game_migrations::table.find(game_id)).execute(conn)?;
+        Ok(())
+    }
+
+    /// The accounts of the players who are still playing a game.
+    fn active_players(&self, game_id: Uuid, conn: &DbConnection) -> anyhow::Result<Vec<Uuid>> {
+        Ok(players::table
+            .select(players::account_id)
+            .filter(players::game_id.eq(game_id))
+            .filter(
+                players::engagement
+                    .eq(PlayerEngagement::Host)
+                    .or(players::engagement.eq(PlayerEngagement::Player)),
+            )
+            .load(conn)?)
+    }
+
+    /// The accounts of the active players who have not yet accepted a game's proposed migration.
+    fn awaiting_migration_votes(
+        &self,
+        game_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<Vec<Uuid>> {
+        let accepted: Vec<Uuid> = game_migration_votes::table
+            .select(game_migration_votes::account_id)
+            .filter(game_migration_votes::game_id.eq(game_id))
+            .filter(game_migration_votes::accepted.eq(true))
+            .load(conn)?;
+        Ok(self
+            .active_players(game_id, conn)?
+            .into_iter()
+            .filter(|player| !accepted.contains(player))
+            .collect())
+    }
+
+    /// Runs each archetype's migration hook on the entities of a game, to find their states in
+    /// another version of the game's universe. The game is read in its own transaction, and the
+    /// hooks are run once it has ended.
+    fn prepare_game_migration(
+        &self,
+        context: &Context,
+        game_id: Uuid,
+        to_version: i32,
+    ) -> anyhow::Result<PreparedMigration> {
+        let (game, game_entities, scripts) = context.transaction(|conn| {
+            let game: data::Game = games::table.find(game_id).get_result(conn)?;
+            self.assert_game_migratable(&game, to_version, conn)?;
+            let pinned_version = |universe_version: i32, archetype_id: Uuid| {
+                universe_version_archetypes::table
+                    .select(universe_version_archetypes::archetype_version)
+                    .filter(universe_version_archetypes::universe_id.eq(game.universe_id))
+                    .filter(universe_version_archetypes::universe_version.eq(universe_version))
+                    .filter(universe_version_archetypes::archetype_id.eq(archetype_id))
+                    .get_result::<i32>(conn)
+            };
+            let game_entities: Vec<data::Entity> = entities::table
+                .filter(entities::game_id.eq(game_id))
+                .order_by(entities::id.asc())
+                .load(conn)?;
+            // Each archetype's script is loaded once, and only if its version changes.
+            let mut scripts: HashMap<Uuid, Option<(i32, String)>> = HashMap::new();
+            for entity in &game_entities {
+                if scripts.contains_key(&entity.archetype_id) {
+                    continue;
+                }
+                let from = pinned_version(game.universe_version, entity.archetype_id)?;
+                let to = pinned_version(to_version, entity.archetype_id)?;
+                let script = if from == to {
+                    None
+                } else {
+                    let script: String = archetype_versions::table
+                        .select(archetype_versions::script)
+                        .filter(archetype_versions::archetype_id.eq(entity.archetype_id))
+                        .filter(archetype_versions::version.eq(to))
+                        .get_result(conn)?;
+                    Some((from, script))
+                };
+                scripts.insert(entity.archetype_id, script);
+            }
+            Ok((game, game_entities, scripts))
+        })?;
+
+        let entities = tokio::task::block_in_place(|| {
+            game_entities
+                .into_iter()
+                .map(|entity| {
+                    let state = match &scripts[&entity.archetype_id] {
+                        Some((from, script)) => engine::migrate(script, *from, &entity.state)?,
+                        None => entity.state.clone(),
+                    };
+                    Ok((entity, state))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        })?;
+        Ok(PreparedMigration {
+            from_version: game.universe_version,
+            to_version,
+            entities,
+        })
+    }
+
+    /// Once every active player has accepted the proposed migration, moves the game to the new
+    /// version of its universe, applying the states found when the migration was prepared. The
+    /// migration is locked while the votes are counted, so that when the last players vote at
+    /// the same time, one of them sees the others' votes.
+    fn migrate_game_if_accepted(
+        &self,
+        game_id: Uuid,
+        prepared: Option<&PreparedMigration>,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let migration: data::GameMigration = match game_migrations::table
+            .find(game_id)
+            .for_update()
+            .get_result(conn)
+            .optional()?
+        {
+            Some(migration) => migration,
+            None => return Ok(()),
+        };
+        if !self.awaiting_migration_votes(game_id, conn)?.is_empty() {
+            return Ok(());
+        }
+
+        let game: data::Game = games::table.find(game_id).for_update().get_result(conn)?;
+        self.assert_game_migratable(&game, migration.universe_version, conn)?;
+        let game_entities: Vec<data::Entity> = entities::table
+            .filter(entities::game_id.eq(game_id))
+            .order_by(entities::id.asc())
+            .load(conn)?;
+        let prepared = prepared
+            .filter(|prepared| prepared.is_current(&game, &migration, &game_entities))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "This game ({}) changed while its migration was being prepared. Try again.",
+                    game_id,
+                )
+            })?;
+        for (entity, state) in &prepared.entities {
+            if *state != entity.state {
+                update(entities::table.find(entity.id))
+                    .set(entities::state.eq(state))
+                    .execute(conn)?;
+            }
+        }
+        update(games::table.find(game_id))
+            .set(games::universe_version.eq(migration.universe_version))
+            .execute(conn)?;
+        self.discard_game_migration(game_id, conn)
+    }
+}
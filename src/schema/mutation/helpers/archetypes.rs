@@ -1,4 +1,5 @@
 use super::Mutation;
+use crate::schema::revision;
 use data::*;
 use diesel::dsl::*;
 use diesel::prelude::*;
@@ -16,4 +17,121 @@ impl Mutation {
             .get_result::<Option<i32>>(conn)?
             .unwrap())
     }
+
+    /// The revision of the most recent version of an archetype.
+    pub fn archetype_revision(
+        &self,
+        archetype_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<String> {
+        let most_recent_version = self.archetype_current_version(archetype_id, conn)?;
+        let current: ArchetypeVersion = archetype_versions::table
+            .find((archetype_id, most_recent_version))
+            .get_result(conn)?;
+        Ok(revision::of(&current.script))
+    }
+
+    /// Ensures that the most recent version of an archetype still has the expected revision, so
+    /// that a stale edit does not overwrite someone else's changes.
+    pub fn assert_archetype_revision(
+        &self,
+        archetype_id: Uuid,
+        expected: &str,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let most_recent_version = self.archetype_current_version(archetype_id, conn)?;
+        let current: ArchetypeVersion = archetype_versions::table
+            .find((archetype_id, most_recent_version))
+            .get_result(conn)?;
+        let current_revision = revision::of(&current.script);
+        anyhow::ensure!(
+            current_revision == expected,
+            "This archetype ({}) has been changed since revision {}. Merge your changes with the current revision ({}) and try again. Its current script is:\n{}",
+            archetype_id,
+            expected,
+            current_revision,
+            current.script,
+        );
+        Ok(())
+    }
+
+    /// Whether any version of an archetype is used by a released version of its universe.
+    pub fn archetype_released(
+        &self,
+        archetype_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<bool> {
+        let same_universe_version = universe_versions::universe_id
+            .eq(universe_version_archetypes::universe_id)
+            .and(universe_versions::version.eq(universe_version_archetypes::universe_version));
+        let released = universe_version_archetypes::table
+            .inner_join(universe_versions::table.on(same_universe_version))
+            .filter(universe_version_archetypes::archetype_id.eq(archetype_id))
+            .filter(universe_versions::released_at.is_not_null());
+        Ok(select(exists(released)).get_result(conn)?)
+    }
+
+    /// Writes a new script for an archetype. If the most recent version of the archetype has been
+    /// released, a new version is created, otherwise the most recent version is updated in place.
+    /// A new version keeps the assets of the version it replaces. The new script is then pinned in
+    /// the unreleased version of the universe.
+    #[rustfmt::skip]
+    pub fn write_archetype_script(
+        &self,
+        archetype: &Archetype,
+        script: &str,
+        conn: &DbConnection,
+    ) -> anyhow::Result<ArchetypeVersion> {
+        anyhow::ensure!(
+            archetype.deleted_at.is_none(),
+            "This archetype ({}) has been deleted",
+            archetype.id,
+        );
+        let most_recent_version = self.archetype_current_version(archetype.id, conn)?;
+        let same_universe_version = universe_versions::universe_id.eq(universe_version_archetypes::universe_id)
+            .and(universe_versions::version.eq(universe_version_archetypes::universe_version));
+        let version_in_use = universe_version_archetypes::table
+            .inner_join(universe_versions::table.on(same_universe_version))
+            .filter(universe_version_archetypes::archetype_id.eq(archetype.id))
+            .filter(universe_version_archetypes::archetype_version.eq(most_recent_version))
+            .filter(universe_versions::released_at.is_not_null());
+        let version_in_use = select(exists(version_in_use)).get_result(conn)?;
+        let archetype_version: data::ArchetypeVersion = if version_in_use {
+            let archetype_version = insert_into(archetype_versions::table)
+                .values((
+                    archetype_versions::archetype_id.eq(archetype.id),
+                    archetype_versions::version.eq(most_recent_version + 1),
+                    archetype_versions::script.eq(script),
+                ))
+                .returning(archetype_versions::all_columns)
+                .get_result(conn)?;
+            archetype_version_assets::table
+                .select((
+                    archetype_version_assets::archetype_id,
+                    (most_recent_version + 1).into_sql::<diesel::sql_types::Integer>(),
+                    archetype_version_assets::name,
+                    archetype_version_assets::asset_hash,
+                ))
+                .filter(archetype_version_assets::archetype_id.eq(archetype.id))
+                .filter(archetype_version_assets::archetype_version.eq(most_recent_version))
+                .insert_into(archetype_version_assets::table)
+                .execute(conn)?;
+            archetype_version
+        } else {
+            update(archetype_versions::table)
+                .set(archetype_versions::script.eq(script))
+                .filter(archetype_versions::archetype_id.eq(archetype.id))
+                .filter(archetype_versions::version.eq(most_recent_version))
+                .returning(archetype_versions::all_columns)
+                .get_result(conn)?
+        };
+        let universe_version = self.unreleased_universe_version(archetype.universe_id, conn)?;
+        update(universe_version_archetypes::table)
+            .filter(universe_version_archetypes::universe_id.eq(universe_version.universe_id))
+            .filter(universe_version_archetypes::universe_version.eq(universe_version.version))
+            .filter(universe_version_archetypes::archetype_id.eq(archetype_version.archetype_id))
+            .set(universe_version_archetypes::archetype_version.eq(archetype_version.version))
+            .execute(conn)?;
+        Ok(archetype_version)
+    }
 }
@@ -32,20 +32,187 @@ impl Mutation {
         conn: &DbConnection,
     ) -> anyhow::Result<()> {
         let contributor = contributors::table
+            .filter(contributors::universe_id.eq(universe_id))
+            .filter(contributors::account_id.eq(account_id))
+            .filter(contributors::role.eq_any(vec![
+                ContributorRole::Owner,
+                ContributorRole::Maintainer,
+                ContributorRole::Contributor,
+            ]));
+        let is_contributor: bool = select(exists(contributor)).get_result(conn)?;
+        anyhow::ensure!(
+            is_contributor,
+            "You ({}) are not a contributor to this universe ({})",
+            account_id,
+            universe_id,
+        );
+        Ok(())
+    }
+
+    pub fn is_universe_maintainer(
+        &self,
+        universe_id: Uuid,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<bool> {
+        let maintainer = contributors::table
             .filter(contributors::universe_id.eq(universe_id))
             .filter(contributors::account_id.eq(account_id))
             .filter(
                 contributors::role
                     .eq(ContributorRole::Owner)
-                    .or(contributors::role.eq(ContributorRole::Contributor)),
+                    .or(contributors::role.eq(ContributorRole::Maintainer)),
             );
-        let is_contributor: bool = select(exists(contributor)).get_result(conn)?;
+        Ok(select(exists(maintainer)).get_result(conn)?)
+    }
+
+    pub fn assert_universe_maintainer(
+        &self,
+        universe_id: Uuid,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
         anyhow::ensure!(
-            is_contributor,
-            "You ({}) are not a contributor to this universe ({})",
+            self.is_universe_maintainer(universe_id, account_id, conn)?,
+            "You ({}) are not a maintainer of this universe ({})",
+            account_id,
+            universe_id,
+        );
+        Ok(())
+    }
+
+    pub fn assert_universe_reviewer(
+        &self,
+        universe_id: Uuid,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let reviewer = contributors::table
+            .filter(contributors::universe_id.eq(universe_id))
+            .filter(contributors::account_id.eq(account_id))
+            .filter(contributors::role.eq_any(vec![
+                ContributorRole::Owner,
+                ContributorRole::Maintainer,
+                ContributorRole::Contributor,
+                ContributorRole::Reviewer,
+            ]));
+        let is_reviewer: bool = select(exists(reviewer)).get_result(conn)?;
+        anyhow::ensure!(
+            is_reviewer,
+            "You ({}) are not a reviewer of this universe ({})",
             account_id,
             universe_id,
         );
         Ok(())
     }
+
+    /// Archetypes may be restricted to a set of editors. Maintainers can always edit every
+    /// archetype, while other contributors can only edit unrestricted archetypes, or those they
+    /// have been made an editor of.
+    pub fn assert_archetype_editor(
+        &self,
+        archetype: &Archetype,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        self.assert_universe_contributor(archetype.universe_id, account_id, conn)?;
+        if self.is_universe_maintainer(archetype.universe_id, account_id, conn)? {
+            return Ok(());
+        }
+        let editors: Vec<Uuid> = archetype_editors::table
+            .select(archetype_editors::account_id)
+            .filter(archetype_editors::archetype_id.eq(archetype.id))
+            .load(conn)?;
+        anyhow::ensure!(
+            editors.is_empty() || editors.contains(&account_id),
+            "You ({}) are not an editor of this archetype ({})",
+            account_id,
+            archetype.id,
+        );
+        Ok(())
+    }
+
+    /// Maps may be restricted to a set of editors, in the same way as archetypes.
+    pub fn assert_map_editor(
+        &self,
+        map: &Map,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        self.assert_universe_contributor(map.universe_id, account_id, conn)?;
+        if self.is_universe_maintainer(map.universe_id, account_id, conn)? {
+            return Ok(());
+        }
+        let editors: Vec<Uuid> = map_editors::table
+            .select(map_editors::account_id)
+            .filter(map_editors::map_id.eq(map.id))
+            .load(conn)?;
+        anyhow::ensure!(
+            editors.is_empty() || editors.contains(&account_id),
+            "You ({}) are not an editor of this map ({})",
+            account_id,
+            map.id,
+        );
+        Ok(())
+    }
+
+    /// Whether an account may see a universe. Private universes may only be seen by the accounts
+    /// involved in them.
+    pub fn universe_visible_to(
+        &self,
+        universe_id: Uuid,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<bool> {
+        let visibility: Option<UniverseVisibility> = universes::table
+            .find(universe_id)
+            .select(universes::visibility)
+            .get_result(conn)
+            .optional()?;
+        match visibility {
+            None => Ok(false),
+            Some(UniverseVisibility::Private) => {
+                let involved = contributors::table
+                    .filter(contributors::universe_id.eq(universe_id))
+                    .filter(contributors::account_id.eq(account_id))
+                    .filter(contributors::role.ne(ContributorRole::Declined));
+                Ok(select(exists(involved)).get_result(conn)?)
+            }
+            Some(..) => Ok(true),
+        }
+    }
+
+    pub fn assert_universe_visible(
+        &self,
+        universe_id: Uuid,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.universe_visible_to(universe_id, account_id, conn)?,
+            "This universe ({}) does not exist",
+            universe_id,
+        );
+        Ok(())
+    }
+
+    pub fn assert_game_host(
+        &self,
+        game_id: Uuid,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let host = players::table
+            .filter(players::game_id.eq(game_id))
+            .filter(players::account_id.eq(account_id))
+            .filter(players::engagement.eq(PlayerEngagement::Host));
+        let is_host: bool = select(exists(host)).get_result(conn)?;
+        anyhow::ensure!(
+            is_host,
+            "You ({}) are not the host of this game ({})",
+            account_id,
+            game_id,
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,71 @@
+use super::Mutation;
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+impl Mutation {
+    /// Records a change to an account's role in a universe, and who made that change. A role of
+    /// `None` means the account is no longer associated with the universe.
+    pub fn record_contributor_change(
+        &self,
+        universe_id: Uuid,
+        account_id: Uuid,
+        performed_by: Uuid,
+        role: Option<ContributorRole>,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        self.insert_contributor_change(universe_id, account_id, Some(performed_by), role, conn)
+    }
+
+    /// Records a change to an account's role in a universe which was made automatically by the
+    /// server, rather than by any account.
+    pub fn record_automatic_contributor_change(
+        &self,
+        universe_id: Uuid,
+        account_id: Uuid,
+        role: Option<ContributorRole>,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        self.insert_contributor_change(universe_id, account_id, None, role, conn)
+    }
+
+    fn insert_contributor_change(
+        &self,
+        universe_id: Uuid,
+        account_id: Uuid,
+        performed_by: Option<Uuid>,
+        role: Option<ContributorRole>,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        insert_into(contributor_changes::table)
+            .values((
+                contributor_changes::universe_id.eq(universe_id),
+                contributor_changes::account_id.eq(account_id),
+                contributor_changes::performed_by.eq(performed_by),
+                contributor_changes::role.eq(role),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn load_contributor(
+        &self,
+        universe_id: Uuid,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<data::Contributor> {
+        contributors::table
+            .filter(contributors::universe_id.eq(universe_id))
+            .filter(contributors::account_id.eq(account_id))
+            .get_result(conn)
+            .optional()?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "That account ({}) is not a contributor to this universe ({})",
+                    account_id,
+                    universe_id,
+                )
+            })
+    }
+}
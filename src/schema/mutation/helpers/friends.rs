@@ -0,0 +1,53 @@
+use super::Mutation;
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+impl Mutation {
+    /// Whether one account has blocked another.
+    pub fn has_blocked(
+        &self,
+        account_id: Uuid,
+        blocked_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<bool> {
+        let block = blocks::table.find((account_id, blocked_id));
+        Ok(select(exists(block)).get_result(conn)?)
+    }
+
+    /// Ensures that an account has not been blocked by the account it is inviting.
+    pub fn assert_not_blocked(
+        &self,
+        account_id: Uuid,
+        invitee_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !self.has_blocked(invitee_id, account_id, conn)?,
+            "That account ({}) is not accepting invitations from you",
+            invitee_id,
+        );
+        Ok(())
+    }
+
+    /// The friendship between two accounts, whichever of them requested it.
+    pub fn load_friendship(
+        &self,
+        account_id: Uuid,
+        other_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<Option<Friendship>> {
+        Ok(friendships::table
+            .filter(
+                friendships::requester_id
+                    .eq(account_id)
+                    .and(friendships::addressee_id.eq(other_id))
+                    .or(friendships::requester_id
+                        .eq(other_id)
+                        .and(friendships::addressee_id.eq(account_id))),
+            )
+            .get_result(conn)
+            .optional()?)
+    }
+}
@@ -0,0 +1,810 @@
+use super::Mutation;
+use crate::engine;
+use crate::schema::{time_control, Database};
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// The results of the scripts that a game's rules call for, worked out before the game is locked
+/// so that no script runs while it is. Each result is kept with the input it was worked out from,
+/// and is only used if the locked game gives the same input.
+pub struct PreparedScripts {
+    universe_version: i32,
+    departures: HashMap<Uuid, (Value, Option<Value>)>,
+    eliminated: Option<(Value, Vec<Uuid>)>,
+    victory: Option<(Value, Option<Vec<engine::Placement>>)>,
+}
+
+impl PreparedScripts {
+    fn new(game: &Game) -> Self {
+        Self {
+            universe_version: game.universe_version,
+            departures: HashMap::new(),
+            eliminated: None,
+            victory: None,
+        }
+    }
+
+    fn changed(&self, game: &Game) -> anyhow::Error {
+        anyhow::anyhow!(
+            "This game ({}) changed while its rules were being run. Try again.",
+            game.id,
+        )
+    }
+
+    /// The result of the departure hook for an entity whose owner is leaving the game.
+    fn depart(&self, game: &Game, entity: &Entity) -> anyhow::Result<Option<Value>> {
+        match self.departures.get(&entity.id) {
+            Some((state, result))
+                if self.universe_version == game.universe_version && *state == entity.state =>
+            {
+                Ok(result.clone())
+            }
+            _ => Err(self.changed(game)),
+        }
+    }
+
+    /// The result of the elimination rule for the game in the given state.
+    fn eliminated(&self, game: &Game, state: &Value) -> anyhow::Result<&[Uuid]> {
+        match &self.eliminated {
+            Some((input, result))
+                if self.universe_version == game.universe_version && input == state =>
+            {
+                Ok(result)
+            }
+            _ => Err(self.changed(game)),
+        }
+    }
+
+    /// The result of the victory condition for the game in the given state.
+    fn victory(
+        &self,
+        game: &Game,
+        state: &Value,
+    ) -> anyhow::Result<Option<Vec<engine::Placement>>> {
+        match &self.victory {
+            Some((input, result))
+                if self.universe_version == game.universe_version && input == state =>
+            {
+                Ok(result.clone())
+            }
+            _ => Err(self.changed(game)),
+        }
+    }
+}
+
+/// A game as it was read, without locking it, along with the scripts its rules may call for.
+pub struct GameSnapshot {
+    game: Game,
+    remaining: Vec<Player>,
+    entities: Vec<Entity>,
+    map_script: String,
+    archetype_scripts: HashMap<Uuid, String>,
+}
+
+impl GameSnapshot {
+    /// Runs the scripts needed for a player to leave the game.
+    pub fn prepare_departure(&self, account_id: Uuid) -> anyhow::Result<PreparedScripts> {
+        let mut prepared = PreparedScripts::new(&self.game);
+        self.depart(&[account_id], &mut prepared)?;
+        Ok(prepared)
+    }
+
+    /// Runs the scripts needed to finish the current turn: the elimination rule, the departure
+    /// hooks of the players it eliminates, and then the victory condition.
+    pub fn prepare_turn(&self) -> anyhow::Result<PreparedScripts> {
+        let mut prepared = PreparedScripts::new(&self.game);
+        let state = script_state(&self.game, &self.remaining, &self.entities);
+        let eliminated = engine::eliminated(&self.map_script, &state)?;
+        prepared.eliminated = Some((state, eliminated.clone()));
+        let remaining: Vec<Player> = self
+            .remaining
+            .iter()
+            .filter(|player| !eliminated.contains(&player.account_id))
+            .cloned()
+            .collect();
+        self.depart(&eliminated, &mut prepared)?;
+        if remaining.len() > 1 {
+            let entities = self.entities_after(&eliminated, &prepared);
+            let state = script_state(&self.game, &remaining, &entities);
+            let placements = engine::victory(&self.map_script, &state)?;
+            prepared.victory = Some((state, placements));
+        }
+        Ok(prepared)
+    }
+
+    /// Runs the scripts needed when the current turn times out: those for the current player to
+    /// leave the game if this timeout makes them forfeit, and otherwise those to finish the turn.
+    pub fn prepare_timeout(&self) -> anyhow::Result<PreparedScripts> {
+        let current = self
+            .remaining
+            .iter()
+            .find(|player| Some(player.turn_order) == self.game.current_turn);
+        match current {
+            Some(player) if forfeits_on_timeout(&self.game, player) => {
+                self.prepare_departure(player.account_id)
+            }
+            _ => self.prepare_turn(),
+        }
+    }
+
+    fn is_leaving(entity: &Entity, accounts: &[Uuid]) -> bool {
+        entity
+            .account_id
+            .map(|account_id| accounts.contains(&account_id))
+            .unwrap_or(false)
+    }
+
+    /// Runs the departure hooks of the entities owned by players who are leaving the game.
+    fn depart(&self, accounts: &[Uuid], prepared: &mut PreparedScripts) -> anyhow::Result<()> {
+        if !matches!(self.game.departure_rule, DepartureRule::Script) {
+            return Ok(());
+        }
+        for entity in &self.entities {
+            if !Self::is_leaving(entity, accounts) {
+                continue;
+            }
+            let script = self
+                .archetype_scripts
+                .get(&entity.archetype_id)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "The archetype ({}) of entity {} is not part of this game's universe",
+                        entity.archetype_id,
+                        entity.id,
+                    )
+                })?;
+            let result = engine::depart(script, &entity.state)?;
+            prepared
+                .departures
+                .insert(entity.id, (entity.state.clone(), result));
+        }
+        Ok(())
+    }
+
+    /// The entities of the game as they will be once the given players have left it.
+    fn entities_after(&self, accounts: &[Uuid], prepared: &PreparedScripts) -> Vec<Entity> {
+        self.entities
+            .iter()
+            .filter_map(|entity| {
+                let mut entity = entity.clone();
+                if !Self::is_leaving(&entity, accounts) {
+                    return Some(entity);
+                }
+                entity.account_id = None;
+                match self.game.departure_rule {
+                    DepartureRule::Neutralize => Some(entity),
+                    DepartureRule::Remove => None,
+                    DepartureRule::Script => {
+                        let state = prepared.departures.get(&entity.id)?.1.clone()?;
+                        entity.state = state;
+                        Some(entity)
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Whether the current player of a game forfeits if their turn times out now.
+fn forfeits_on_timeout(game: &Game, player: &Player) -> bool {
+    game.timeout_action == TimeoutAction::Forfeit
+        && player.timeouts + 1 >= game.forfeit_after.unwrap_or(1)
+}
+
+/// The state of a game as given to the scripts of its map: the game's own state, its remaining
+/// players and its entities.
+fn script_state(game: &Game, remaining: &[Player], game_entities: &[Entity]) -> Value {
+    serde_json::json!({
+        "state": game.state,
+        "players": remaining
+            .iter()
+            .map(|player| serde_json::json!({
+                "account": player.account_id,
+                "state": player.state,
+            }))
+            .collect::<Vec<_>>(),
+        "entities": game_entities
+            .iter()
+            .map(|entity| serde_json::json!({
+                "id": entity.id,
+                "archetype": entity.archetype_id,
+                "account": entity.account_id,
+                "state": entity.state,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+impl Mutation {
+    /// The number of seats in a game that are not yet taken by a player or an invitation.
+    pub fn game_open_seats(&self, game: &Game, conn: &DbConnection) -> anyhow::Result<i32> {
+        let taken: i64 = players::table
+            .filter(players::game_id.eq(game.id))
+            .filter(players::engagement.eq_any(PlayerEngagement::TAKING_SEAT.to_vec()))
+            .count()
+            .get_result(conn)?;
+        Ok(game.seats - taken as i32)
+    }
+
+    /// Starts a game once every one of its seats has been filled by a player.
+    pub fn start_game_if_full(&self, game_id: Uuid, conn: &DbConnection) -> anyhow::Result<()> {
+        let game: Game = games::table.find(game_id).get_result(conn)?;
+        if game.started_at.is_some() {
+            return Ok(());
+        }
+        let seated: i64 = players::table
+            .filter(players::game_id.eq(game_id))
+            .filter(
+                players::engagement.eq_any(vec![PlayerEngagement::Host, PlayerEngagement::Player]),
+            )
+            .count()
+            .get_result(conn)?;
+        if seated as i32 >= game.seats {
+            update(&game).set(games::started_at.eq(now)).execute(conn)?;
+            update(players::table.filter(players::game_id.eq(game_id)))
+                .set(players::time_bank.eq(game.time_bank))
+                .execute(conn)?;
+            self.advance_turn(game_id, conn)?;
+        }
+        Ok(())
+    }
+
+    /// Passes the turn to the next active player in turn order, starting their clock, and lets
+    /// them know it is their turn. If no turn has been taken yet, the turn goes to the first
+    /// player. Nothing happens once the game has ended.
+    pub fn advance_turn(&self, game_id: Uuid, conn: &DbConnection) -> anyhow::Result<()> {
+        let game: Game = games::table.find(game_id).get_result(conn)?;
+        if game.ended_at.is_some() {
+            return Ok(());
+        }
+        let turn_orders: Vec<i32> = players::table
+            .select(players::turn_order)
+            .filter(players::game_id.eq(game_id))
+            .filter(
+                players::engagement.eq_any(vec![PlayerEngagement::Host, PlayerEngagement::Player]),
+            )
+            .order_by(players::turn_order.asc())
+            .load(conn)?;
+        let next_turn = match game.current_turn {
+            Some(current) => turn_orders
+                .iter()
+                .find(|turn| **turn > current)
+                .or_else(|| turn_orders.first())
+                .copied(),
+            None => turn_orders.first().copied(),
+        };
+        update(&game)
+            .set((
+                games::current_turn.eq(next_turn),
+                games::turn_started_at.eq(now),
+            ))
+            .execute(conn)?;
+        if let Some(next_turn) = next_turn {
+            let next_player: Uuid = players::table
+                .select(players::account_id)
+                .filter(players::game_id.eq(game_id))
+                .filter(players::turn_order.eq(next_turn))
+                .get_result(conn)?;
+            self.notify(
+                &[next_player],
+                NotificationKind::YourTurn,
+                Some(game_id),
+                Some(game.universe_id),
+                conn,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Ends the current turn of a game on behalf of the player whose turn it is, taking any
+    /// overtime from their time bank. The scripts must have been prepared by `prepare_turn`.
+    pub fn end_current_turn(
+        &self,
+        game: &Game,
+        prepared: &PreparedScripts,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let player = self.current_player(game, conn)?;
+        let time_bank = time_control::bank_after_turn(game, &player, chrono::Utc::now());
+        update(&player)
+            .set(players::time_bank.eq(time_bank))
+            .execute(conn)?;
+        self.finish_turn(game.id, prepared, conn)
+    }
+
+    /// Closes out the current turn of a game, whether it was ended or skipped: the map's rules
+    /// eliminate players and decide whether the game has been won, then play passes on.
+    fn finish_turn(
+        &self,
+        game_id: Uuid,
+        prepared: &PreparedScripts,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        self.eliminate_players(game_id, prepared, conn)?;
+        self.evaluate_victory(game_id, prepared, conn)?;
+        self.advance_turn(game_id, conn)
+    }
+
+    /// The player whose turn it currently is in a game.
+    pub fn current_player(&self, game: &Game, conn: &DbConnection) -> anyhow::Result<Player> {
+        let current_turn = game
+            .current_turn
+            .ok_or_else(|| anyhow::anyhow!("This game ({}) is not in progress", game.id))?;
+        Ok(players::table
+            .filter(players::game_id.eq(game.id))
+            .filter(players::turn_order.eq(current_turn))
+            .get_result(conn)?)
+    }
+
+    /// Applies the consequences of a timeout to every game whose current turn has run out of
+    /// time. The player who timed out loses their turn, and if the game forfeits players after a
+    /// number of timeouts, they may lose their place in the game. Returns the number of turns
+    /// that timed out.
+    pub fn expire_turns(&self, database: &Database) -> anyhow::Result<usize> {
+        // Only games whose turn has already run out are found, following the same rule as
+        // time_control::turn_deadline.
+        let expired_games: Vec<Uuid> = database.transaction(|conn| {
+            Ok(games::table
+                .inner_join(
+                    players::table.on(players::game_id
+                        .eq(games::id)
+                        .and(games::current_turn.eq(players::turn_order.nullable()))),
+                )
+                .select(games::id)
+                .filter(games::started_at.is_not_null())
+                .filter(games::ended_at.is_null())
+                .filter(
+                    games::turn_limit
+                        .is_not_null()
+                        .or(games::time_bank.is_not_null()),
+                )
+                .filter(sql::<diesel::sql_types::Bool>(
+                    "games.turn_started_at + make_interval(secs => coalesce(games.turn_limit, 0) + coalesce(players.time_bank, 0)) <= now()",
+                ))
+                .load(conn)?)
+        })?;
+        let mut expired = 0;
+        for game_id in expired_games {
+            // Each game is expired on its own, so that one game failing does not hold up the
+            // others.
+            match self.expire_game_turn(game_id, database) {
+                Ok(true) => expired += 1,
+                Ok(false) => {}
+                Err(error) => {
+                    log::warn!("Failed to expire the turn of game {}: {}", game_id, error)
+                }
+            }
+        }
+        Ok(expired)
+    }
+
+    /// Expires the current turn of a game if it has run out of time, returning whether it had.
+    /// The game's scripts are run before it is locked, and a game which is already locked is left
+    /// for the next run.
+    fn expire_game_turn(&self, game_id: Uuid, database: &Database) -> anyhow::Result<bool> {
+        let snapshot = database.transaction(|conn| {
+            let game: Game = games::table.find(game_id).get_result(conn)?;
+            self.game_snapshot(game, conn)
+        })?;
+        let prepared = snapshot.prepare_timeout()?;
+        database.transaction(|conn| {
+            let game: Option<Game> = games::table
+                .find(game_id)
+                .filter(games::ended_at.is_null())
+                .for_update()
+                .skip_locked()
+                .get_result(conn)
+                .optional()?;
+            match game {
+                Some(game) => self.expire_turn(&game, &prepared, conn),
+                None => Ok(false),
+            }
+        })
+    }
+
+    /// Applies the consequences of a timeout to a game if its current turn has run out of time,
+    /// returning whether it had.
+    fn expire_turn(
+        &self,
+        game: &Game,
+        prepared: &PreparedScripts,
+        conn: &DbConnection,
+    ) -> anyhow::Result<bool> {
+        let player = self.current_player(game, conn)?;
+        match time_control::turn_deadline(game, &player) {
+            Some(deadline) if deadline <= chrono::Utc::now() => {}
+            _ => return Ok(false),
+        }
+        let forfeits = forfeits_on_timeout(game, &player);
+        update(&player)
+            .set((
+                players::timeouts.eq(player.timeouts + 1),
+                players::time_bank.eq(player.time_bank.map(|_| 0)),
+            ))
+            .execute(conn)?;
+        if forfeits {
+            self.depart_game(game, &player, PlayerEngagement::Forfeited, prepared, conn)?;
+        } else {
+            self.finish_turn(game.id, prepared, conn)?;
+        }
+        Ok(true)
+    }
+
+    /// Takes a player out of a game that is in progress, leaving them with the given engagement.
+    /// The game's departure rule decides what becomes of the entities they own. If the host
+    /// departs, the next player in turn order becomes the host. If it was the player's turn, play
+    /// passes to the next player, and if only one player remains, the game ends. The scripts must
+    /// have been prepared by `prepare_departure`.
+    pub fn depart_game(
+        &self,
+        game: &Game,
+        player: &Player,
+        engagement: PlayerEngagement,
+        prepared: &PreparedScripts,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        self.leave_game(game, player, engagement, prepared, conn)?;
+        self.end_game_if_decided(game.id, conn)?;
+        if game.current_turn == Some(player.turn_order) {
+            self.advance_turn(game.id, conn)?;
+        }
+        Ok(())
+    }
+
+    /// Takes a player out of a game as for `depart_game`, but leaves it to the caller to end the
+    /// game or pass on the turn.
+    fn leave_game(
+        &self,
+        game: &Game,
+        player: &Player,
+        engagement: PlayerEngagement,
+        prepared: &PreparedScripts,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        update(player)
+            .set((
+                players::engagement.eq(engagement),
+                players::departed_at.eq(now),
+            ))
+            .execute(conn)?;
+
+        let owned_entities: Vec<Entity> = entities::table
+            .filter(entities::game_id.eq(game.id))
+            .filter(entities::account_id.eq(player.account_id))
+            .load(conn)?;
+        for entity in owned_entities {
+            match game.departure_rule {
+                DepartureRule::Neutralize => {
+                    update(entities::table.find(entity.id))
+                        .set(entities::account_id.eq(None::<Uuid>))
+                        .execute(conn)?;
+                }
+                DepartureRule::Remove => {
+                    delete(entities::table.find(entity.id)).execute(conn)?;
+                }
+                DepartureRule::Script => match prepared.depart(game, &entity)? {
+                    Some(state) => {
+                        update(entities::table.find(entity.id))
+                            .set((
+                                entities::account_id.eq(None::<Uuid>),
+                                entities::state.eq(state),
+                            ))
+                            .execute(conn)?;
+                    }
+                    None => {
+                        delete(entities::table.find(entity.id)).execute(conn)?;
+                    }
+                },
+            }
+        }
+
+        if player.engagement == PlayerEngagement::Host {
+            let next_host: Option<Player> = players::table
+                .filter(players::game_id.eq(game.id))
+                .filter(players::engagement.eq(PlayerEngagement::Player))
+                .order_by(players::turn_order.asc())
+                .first(conn)
+                .optional()?;
+            if let Some(next_host) = next_host {
+                update(&next_host)
+                    .set(players::engagement.eq(PlayerEngagement::Host))
+                    .execute(conn)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes out of a game every remaining player who the elimination rule of its map reports as
+    /// eliminated, ending the game if no more than one player remains. Play is not passed on, so
+    /// this should be followed by `advance_turn`.
+    pub fn eliminate_players(
+        &self,
+        game_id: Uuid,
+        prepared: &PreparedScripts,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let game: Game = games::table.find(game_id).get_result(conn)?;
+        if game.ended_at.is_some() {
+            return Ok(());
+        }
+        let remaining = self.remaining_players(game_id, conn)?;
+        let state = self.game_state(&game, &remaining, conn)?;
+        let eliminated = prepared.eliminated(&game, &state)?;
+        for player in &remaining {
+            if eliminated.contains(&player.account_id) {
+                self.leave_game(&game, player, PlayerEngagement::Eliminated, prepared, conn)?;
+            }
+        }
+        self.end_game_if_decided(game_id, conn)
+    }
+
+    /// Ends a game once no more than one player remains in it. The remaining player places first,
+    /// and the others place in the reverse of the order they left in.
+    pub fn end_game_if_decided(&self, game_id: Uuid, conn: &DbConnection) -> anyhow::Result<()> {
+        let game: Game = games::table.find(game_id).get_result(conn)?;
+        if game.started_at.is_none() || game.ended_at.is_some() {
+            return Ok(());
+        }
+        let remaining: Vec<Player> = players::table
+            .filter(players::game_id.eq(game_id))
+            .filter(
+                players::engagement.eq_any(vec![PlayerEngagement::Host, PlayerEngagement::Player]),
+            )
+            .load(conn)?;
+        if remaining.len() > 1 {
+            return Ok(());
+        }
+        let placements = remaining
+            .iter()
+            .map(|player| engine::Placement {
+                account: player.account_id,
+                placement: 1,
+                score: None,
+            })
+            .collect();
+        self.finish_game(&game, placements, conn)
+    }
+
+    /// The script of the version of the map a game is played on.
+    pub fn game_map_script(&self, game: &Game, conn: &DbConnection) -> anyhow::Result<String> {
+        Ok(universe_version_maps::table
+            .inner_join(
+                map_versions::table.on(map_versions::map_id
+                    .eq(universe_version_maps::map_id)
+                    .and(map_versions::version.eq(universe_version_maps::map_version))),
+            )
+            .select(map_versions::script)
+            .filter(universe_version_maps::universe_id.eq(game.universe_id))
+            .filter(universe_version_maps::universe_version.eq(game.universe_version))
+            .filter(universe_version_maps::map_id.eq(game.map_id))
+            .get_result(conn)?)
+    }
+
+    /// Ensures that a game does not have more seats than the number of players its map is
+    /// designed for. Maps that do not declare a player count allow any number of players. The
+    /// player count is recorded when the map's version is released, or by the count_map_players
+    /// job for versions released before it was, which allow any number of players until then.
+    pub fn assert_seats_within_map(
+        &self,
+        game: &Game,
+        seats: i32,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let player_count: Option<i32> = universe_version_maps::table
+            .inner_join(
+                map_versions::table.on(map_versions::map_id
+                    .eq(universe_version_maps::map_id)
+                    .and(map_versions::version.eq(universe_version_maps::map_version))),
+            )
+            .select(map_versions::players)
+            .filter(universe_version_maps::universe_id.eq(game.universe_id))
+            .filter(universe_version_maps::universe_version.eq(game.universe_version))
+            .filter(universe_version_maps::map_id.eq(game.map_id))
+            .get_result(conn)?;
+        if let Some(player_count) = player_count {
+            anyhow::ensure!(
+                seats <= player_count,
+                "This game's map ({}) is for at most {} players",
+                game.map_id,
+                player_count,
+            );
+        }
+        Ok(())
+    }
+
+    /// The players who are still playing a game, in turn order.
+    fn remaining_players(&self, game_id: Uuid, conn: &DbConnection) -> anyhow::Result<Vec<Player>> {
+        Ok(players::table
+            .filter(players::game_id.eq(game_id))
+            .filter(
+                players::engagement.eq_any(vec![PlayerEngagement::Host, PlayerEngagement::Player]),
+            )
+            .order_by(players::turn_order.asc())
+            .load(conn)?)
+    }
+
+    /// The state of a game as given to the scripts of its map.
+    fn game_state(
+        &self,
+        game: &Game,
+        remaining: &[Player],
+        conn: &DbConnection,
+    ) -> anyhow::Result<Value> {
+        let game_entities: Vec<Entity> = entities::table
+            .filter(entities::game_id.eq(game.id))
+            .order_by(entities::id.asc())
+            .load(conn)?;
+        Ok(script_state(game, remaining, &game_entities))
+    }
+
+    /// Reads a game, without locking it, along with the scripts its rules may call for, so that
+    /// they can be run before the game is locked.
+    pub fn game_snapshot(&self, game: Game, conn: &DbConnection) -> anyhow::Result<GameSnapshot> {
+        let remaining = self.remaining_players(game.id, conn)?;
+        let game_entities: Vec<Entity> = entities::table
+            .filter(entities::game_id.eq(game.id))
+            .order_by(entities::id.asc())
+            .load(conn)?;
+        let map_script = self.game_map_script(&game, conn)?;
+        // Archetype scripts are only needed for departure hooks, and each is loaded once.
+        let archetype_scripts = if matches!(game.departure_rule, DepartureRule::Script) {
+            let archetype_ids: HashSet<Uuid> = game_entities
+                .iter()
+                .map(|entity| entity.archetype_id)
+                .collect();
+            universe_version_archetypes::table
+                .inner_join(
+                    archetype_versions::table.on(archetype_versions::archetype_id
+                        .eq(universe_version_archetypes::archetype_id)
+                        .and(
+                            archetype_versions::version
+                                .eq(universe_version_archetypes::archetype_version),
+                        )),
+                )
+                .select((
+                    universe_version_archetypes::archetype_id,
+                    archetype_versions::script,
+                ))
+                .filter(universe_version_archetypes::universe_id.eq(game.universe_id))
+                .filter(universe_version_archetypes::universe_version.eq(game.universe_version))
+                .filter(
+                    universe_version_archetypes::archetype_id
+                        .eq_any(archetype_ids.into_iter().collect::<Vec<_>>()),
+                )
+                .load::<(Uuid, String)>(conn)?
+                .into_iter()
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        Ok(GameSnapshot {
+            game,
+            remaining,
+            entities: game_entities,
+            map_script,
+            archetype_scripts,
+        })
+    }
+
+    /// Checks the victory condition of a game's map, and ends the game if it has been met.
+    pub fn evaluate_victory(
+        &self,
+        game_id: Uuid,
+        prepared: &PreparedScripts,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let game: Game = games::table.find(game_id).get_result(conn)?;
+        if game.ended_at.is_some() {
+            return Ok(());
+        }
+        let remaining = self.remaining_players(game_id, conn)?;
+        let state = self.game_state(&game, &remaining, conn)?;
+        let placements = match prepared.victory(&game, &state)? {
+            Some(placements) => placements,
+            None => return Ok(()),
+        };
+        let mut placed = HashSet::new();
+        for placement in &placements {
+            anyhow::ensure!(
+                remaining
+                    .iter()
+                    .any(|player| player.account_id == placement.account),
+                "The victory condition of this game's map ({}) placed an account that is not playing",
+                game.map_id,
+            );
+            anyhow::ensure!(
+                placed.insert(placement.account),
+                "The victory condition of this game's map ({}) placed a player more than once",
+                game.map_id,
+            );
+            anyhow::ensure!(
+                placement.placement >= 1,
+                "The victory condition of this game's map ({}) gave a placement below 1",
+                game.map_id,
+            );
+        }
+        anyhow::ensure!(
+            placed.len() == remaining.len(),
+            "The victory condition of this game's map ({}) did not place every player",
+            game.map_id,
+        );
+        self.finish_game(&game, placements, conn)
+    }
+
+    /// Ends a game, recording where each player placed. Players who left the game before it ended
+    /// are placed after those given, in the reverse of the order they left in, and those who left
+    /// at the same time share a placement. Players removed by the host are neither placed nor
+    /// rated.
+    fn finish_game(
+        &self,
+        game: &Game,
+        mut placements: Vec<engine::Placement>,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        update(game)
+            .set((games::ended_at.eq(now), games::current_turn.eq(None::<i32>)))
+            .execute(conn)?;
+
+        let departed: Vec<Player> = players::table
+            .filter(players::game_id.eq(game.id))
+            .filter(players::engagement.eq_any(vec![
+                PlayerEngagement::Resigned,
+                PlayerEngagement::Forfeited,
+                PlayerEngagement::Eliminated,
+            ]))
+            .order_by(players::departed_at.desc())
+            .load(conn)?;
+        let last_placement = placements
+            .iter()
+            .map(|placement| placement.placement)
+            .max()
+            .unwrap_or(0);
+        // Players who left in the same transaction, such as those eliminated together, have the
+        // same departure time. They share a placement, and the placements they share are skipped.
+        let mut placement = last_placement;
+        for (index, player) in departed.iter().enumerate() {
+            if index == 0 || departed[index - 1].departed_at != player.departed_at {
+                placement = last_placement + index as i32 + 1;
+            }
+            placements.push(engine::Placement {
+                account: player.account_id,
+                placement,
+                score: None,
+            });
+        }
+
+        for placement in &placements {
+            insert_into(game_results::table)
+                .values((
+                    game_results::game_id.eq(game.id),
+                    game_results::account_id.eq(placement.account),
+                    game_results::placement.eq(placement.placement),
+                    game_results::score.eq(placement.score),
+                ))
+                .execute(conn)?;
+        }
+        let account_ids: Vec<Uuid> = placements
+            .iter()
+            .map(|placement| placement.account)
+            .collect();
+        self.notify(
+            &account_ids,
+            NotificationKind::GameFinished,
+            Some(game.id),
+            Some(game.universe_id),
+            conn,
+        )?;
+        if placements.len() > 1 {
+            let placements: Vec<(Uuid, i32)> = placements
+                .iter()
+                .map(|placement| (placement.account, placement.placement))
+                .collect();
+            self.rate_game(game, &placements, conn)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use super::Mutation;
+use crate::engine;
+use crate::schema::Database;
+use chrono::{Duration, Utc};
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+/// How long an invitation to contribute to a universe or to join a game may go unanswered.
+const INVITATION_LIFETIME_DAYS: i64 = 30;
+/// How long a game may wait in its lobby for players before it is abandoned.
+const LOBBY_LIFETIME_DAYS: i64 = 14;
+/// How many map versions have their player count worked out each time the job runs.
+const MAP_PLAYER_COUNT_BATCH: i64 = 50;
+
+impl Mutation {
+    /// Deletes emails that were never verified and are no longer protected, so that their
+    /// addresses may be claimed by someone else. An email which an account logs in with is kept,
+    /// as the account would otherwise be left with an address that someone else could claim.
+    /// Returns the number of emails deleted.
+    pub fn purge_unverified_emails(&self, conn: &DbConnection) -> anyhow::Result<usize> {
+        let login_addresses = logins::table.select(logins::email_address);
+        let expired = emails::table
+            .filter(emails::verified_at.is_null())
+            .filter(emails::protected_until.le(now))
+            .filter(not(emails::address.eq_any(login_addresses)));
+        Ok(delete(expired).execute(conn)?)
+    }
+
+    /// Declines invitations to contribute to universes and to join games which have gone
+    /// unanswered for too long. The change is recorded as made automatically, rather than by the
+    /// invitee. Returns the number of invitations expired.
+    pub fn expire_invitations(&self, conn: &DbConnection) -> anyhow::Result<usize> {
+        let cutoff = Utc::now() - Duration::days(INVITATION_LIFETIME_DAYS);
+
+        let stale_contributors: Vec<Contributor> = contributors::table
+            .filter(contributors::role.eq(ContributorRole::Pending))
+            .filter(contributors::created_at.lt(cutoff))
+            .for_update()
+            .skip_locked()
+            .load(conn)?;
+        for contributor in &stale_contributors {
+            update(contributor)
+                .set(contributors::role.eq(ContributorRole::Declined))
+                .execute(conn)?;
+            self.record_automatic_contributor_change(
+                contributor.universe_id,
+                contributor.account_id,
+                Some(ContributorRole::Declined),
+                conn,
+            )?;
+        }
+
+        let stale_players: Vec<Player> = players::table
+            .filter(players::engagement.eq(PlayerEngagement::Pending))
+            .filter(players::created_at.lt(cutoff))
+            .for_update()
+            .skip_locked()
+            .load(conn)?;
+        for player in &stale_players {
+            update(player)
+                .set(players::engagement.eq(PlayerEngagement::Declined))
+                .execute(conn)?;
+            update(games::table.find(player.game_id))
+                .filter(games::started_at.is_null())
+                .set(games::seats.eq(games::seats - 1))
+                .execute(conn)?;
+            self.start_game_if_full(player.game_id, conn)?;
+        }
+
+        Ok(stale_contributors.len() + stale_players.len())
+    }
+
+    /// Deletes games which have waited too long in their lobby without filling their seats, and
+    /// lets the players who had joined or been invited know. Returns the number of games deleted.
+    pub fn close_abandoned_lobbies(&self, conn: &DbConnection) -> anyhow::Result<usize> {
+        let cutoff = Utc::now() - Duration::days(LOBBY_LIFETIME_DAYS);
+        let abandoned: Vec<Game> = games::table
+            .filter(games::started_at.is_null())
+            .filter(games::created_at.lt(cutoff))
+            .for_update()
+            .skip_locked()
+            .load(conn)?;
+        for game in &abandoned {
+            // The game is deleted, so the notification only refers to its universe.
+            let account_ids: Vec<Uuid> = players::table
+                .select(players::account_id)
+                .filter(players::game_id.eq(game.id))
+                .filter(players::engagement.eq_any(vec![
+                    PlayerEngagement::Host,
+                    PlayerEngagement::Player,
+                    PlayerEngagement::Pending,
+                ]))
+                .load(conn)?;
+            self.notify(
+                &account_ids,
+                NotificationKind::LobbyClosed,
+                None,
+                Some(game.universe_id),
+                conn,
+            )?;
+            delete(game_migration_votes::table.filter(game_migration_votes::game_id.eq(game.id)))
+                .execute(conn)?;
+            delete(game_migrations::table.find(game.id)).execute(conn)?;
+            delete(invites::table.filter(invites::game_id.eq(game.id))).execute(conn)?;
+            delete(notifications::table.filter(notifications::game_id.eq(game.id)))
+                .execute(conn)?;
+            delete(players::table.filter(players::game_id.eq(game.id))).execute(conn)?;
+            delete(game).execute(conn)?;
+        }
+        Ok(abandoned.len())
+    }
+
+    /// Records the number of players that released map versions are designed for, for versions
+    /// released before it was recorded on release. The scripts are run outside of any
+    /// transaction, and a count is only recorded if the script has not changed in the meantime.
+    /// Returns the number of map versions counted.
+    pub fn count_map_players(&self, database: &Database) -> anyhow::Result<usize> {
+        let uncounted: Vec<MapVersion> = database.transaction(|conn| {
+            Ok(map_versions::table
+                .inner_join(
+                    universe_version_maps::table.on(universe_version_maps::map_id
+                        .eq(map_versions::map_id)
+                        .and(universe_version_maps::map_version.eq(map_versions::version))),
+                )
+                .inner_join(
+                    universe_versions::table.on(universe_versions::universe_id
+                        .eq(universe_version_maps::universe_id)
+                        .and(
+                            universe_versions::version.eq(universe_version_maps::universe_version),
+                        )),
+                )
+                .select(map_versions::all_columns)
+                .distinct()
+                .filter(universe_versions::released_at.is_not_null())
+                .filter(map_versions::players_counted_at.is_null())
+                .order_by((map_versions::map_id.asc(), map_versions::version.asc()))
+                .limit(MAP_PLAYER_COUNT_BATCH)
+                .load(conn)?)
+        })?;
+        let mut counted = 0;
+        for map_version in uncounted {
+            let players = match engine::player_count(&map_version.script) {
+                Ok(players) => players,
+                // A version whose count cannot be worked out allows any number of players, rather
+                // than being retried on every run.
+                Err(error) => {
+                    log::warn!(
+                        "Failed to count the players of map {} version {}: {}",
+                        map_version.map_id,
+                        map_version.version,
+                        error,
+                    );
+                    None
+                }
+            };
+            counted += database.transaction(|conn| {
+                Ok(update(
+                    map_versions::table
+                        .find((map_version.map_id, map_version.version))
+                        .filter(map_versions::script.eq(&map_version.script))
+                        .filter(map_versions::players_counted_at.is_null()),
+                )
+                .set((
+                    map_versions::players.eq(players),
+                    map_versions::players_counted_at.eq(now),
+                ))
+                .execute(conn)?)
+            })?;
+        }
+        Ok(counted)
+    }
+}
@@ -1,4 +1,5 @@
 use super::Mutation;
+use crate::schema::revision;
 use data::*;
 use diesel::dsl::*;
 use diesel::prelude::*;
@@ -12,4 +13,113 @@ impl Mutation {
             .get_result::<Option<i32>>(conn)?
             .unwrap())
     }
+
+    /// The revision of the most recent version of a map.
+    pub fn map_revision(&self, map_id: Uuid, conn: &DbConnection) -> anyhow::Result<String> {
+        let most_recent_version = self.map_current_version(map_id, conn)?;
+        let current: MapVersion = map_versions::table
+            .find((map_id, most_recent_version))
+            .get_result(conn)?;
+        Ok(revision::of(&current.script))
+    }
+
+    /// Ensures that the most recent version of a map still has the expected revision, so that
+    /// a stale edit does not overwrite someone else's changes.
+    pub fn assert_map_revision(
+        &self,
+        map_id: Uuid,
+        expected: &str,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let most_recent_version = self.map_current_version(map_id, conn)?;
+        let current: MapVersion = map_versions::table
+            .find((map_id, most_recent_version))
+            .get_result(conn)?;
+        let current_revision = revision::of(&current.script);
+        anyhow::ensure!(
+            current_revision == expected,
+            "This map ({}) has been changed since revision {}. Merge your changes with the current revision ({}) and try again. Its current script is:\n{}",
+            map_id,
+            expected,
+            current_revision,
+            current.script,
+        );
+        Ok(())
+    }
+
+    /// Whether any version of a map is used by a released version of its universe.
+    pub fn map_released(&self, map_id: Uuid, conn: &DbConnection) -> anyhow::Result<bool> {
+        let same_universe_version = universe_versions::universe_id
+            .eq(universe_version_maps::universe_id)
+            .and(universe_versions::version.eq(universe_version_maps::universe_version));
+        let released = universe_version_maps::table
+            .inner_join(universe_versions::table.on(same_universe_version))
+            .filter(universe_version_maps::map_id.eq(map_id))
+            .filter(universe_versions::released_at.is_not_null());
+        Ok(select(exists(released)).get_result(conn)?)
+    }
+
+    /// Writes a new script for a map. If the most recent version of the map has been
+    /// released, a new version is created, otherwise the most recent version is updated in place.
+    /// A new version keeps the assets of the version it replaces. The new script is then pinned in
+    /// the unreleased version of the universe.
+    #[rustfmt::skip]
+    pub fn write_map_script(
+        &self,
+        map: &Map,
+        script: &str,
+        conn: &DbConnection,
+    ) -> anyhow::Result<MapVersion> {
+        anyhow::ensure!(
+            map.deleted_at.is_none(),
+            "This map ({}) has been deleted",
+            map.id,
+        );
+        let most_recent_version = self.map_current_version(map.id, conn)?;
+        let same_universe_version = universe_versions::universe_id.eq(universe_version_maps::universe_id)
+            .and(universe_versions::version.eq(universe_version_maps::universe_version));
+        let version_in_use = universe_version_maps::table
+            .inner_join(universe_versions::table.on(same_universe_version))
+            .filter(universe_version_maps::map_id.eq(map.id))
+            .filter(universe_version_maps::map_version.eq(most_recent_version))
+            .filter(universe_versions::released_at.is_not_null());
+        let version_in_use = select(exists(version_in_use)).get_result(conn)?;
+        let map_version: data::MapVersion = if version_in_use {
+            let map_version = insert_into(map_versions::table)
+                .values((
+                    map_versions::map_id.eq(map.id),
+                    map_versions::version.eq(most_recent_version + 1),
+                    map_versions::script.eq(script),
+                ))
+                .returning(map_versions::all_columns)
+                .get_result(conn)?;
+            map_version_assets::table
+                .select((
+                    map_version_assets::map_id,
+                    (most_recent_version + 1).into_sql::<diesel::sql_types::Integer>(),
+                    map_version_assets::name,
+                    map_version_assets::asset_hash,
+                ))
+                .filter(map_version_assets::map_id.eq(map.id))
+                .filter(map_version_assets::map_version.eq(most_recent_version))
+                .insert_into(map_version_assets::table)
+                .execute(conn)?;
+            map_version
+        } else {
+            update(map_versions::table)
+                .set(map_versions::script.eq(script))
+                .filter(map_versions::map_id.eq(map.id))
+                .filter(map_versions::version.eq(most_recent_version))
+                .returning(map_versions::all_columns)
+                .get_result(conn)?
+        };
+        let universe_version = self.unreleased_universe_version(map.universe_id, conn)?;
+        update(universe_version_maps::table)
+            .filter(universe_version_maps::universe_id.eq(universe_version.universe_id))
+            .filter(universe_version_maps::universe_version.eq(universe_version.version))
+            .filter(universe_version_maps::map_id.eq(map_version.map_id))
+            .set(universe_version_maps::map_version.eq(map_version.version))
+            .execute(conn)?;
+        Ok(map_version)
+    }
 }
@@ -2,5 +2,11 @@ use super::*;
 
 mod archetypes;
 mod authorization;
+mod contributors;
+mod friends;
+mod games;
+mod maintenance;
 mod maps;
+mod notifications;
+mod ratings;
 mod universes;
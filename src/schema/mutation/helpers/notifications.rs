@@ -0,0 +1,66 @@
+use super::Mutation;
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+impl Mutation {
+    /// Adds a notification to the inbox of each of the given accounts.
+    pub fn notify(
+        &self,
+        account_ids: &[Uuid],
+        kind: NotificationKind,
+        game_id: Option<Uuid>,
+        universe_id: Option<Uuid>,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let notifications: Vec<_> = account_ids
+            .iter()
+            .map(|account_id| {
+                (
+                    notifications::account_id.eq(*account_id),
+                    notifications::kind.eq(kind),
+                    notifications::game_id.eq(game_id),
+                    notifications::universe_id.eq(universe_id),
+                )
+            })
+            .collect();
+        insert_into(notifications::table)
+            .values(&notifications)
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Notifies the contributors to a universe and the accounts that have favorited it that a new
+    /// version has been released. The account that released it is not notified.
+    pub fn notify_universe_released(
+        &self,
+        universe_id: Uuid,
+        released_by: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let mut account_ids: Vec<Uuid> = contributors::table
+            .select(contributors::account_id)
+            .filter(contributors::universe_id.eq(universe_id))
+            .filter(
+                contributors::role
+                    .ne_all(vec![ContributorRole::Pending, ContributorRole::Declined]),
+            )
+            .load(conn)?;
+        let favorited_by: Vec<Uuid> = universe_favorites::table
+            .select(universe_favorites::account_id)
+            .filter(universe_favorites::universe_id.eq(universe_id))
+            .load(conn)?;
+        account_ids.extend(favorited_by);
+        account_ids.sort();
+        account_ids.dedup();
+        account_ids.retain(|account_id| *account_id != released_by);
+        self.notify(
+            &account_ids,
+            NotificationKind::UniverseReleased,
+            None,
+            Some(universe_id),
+            conn,
+        )
+    }
+}
@@ -0,0 +1,99 @@
+use super::Mutation;
+use crate::rating::Rating;
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+impl Mutation {
+    /// Updates the ratings of the players of a finished game in the game's universe. Each player
+    /// is given a placement, where a lower placement is better and equal placements are draws.
+    pub fn rate_game(
+        &self,
+        game: &Game,
+        placements: &[(Uuid, i32)],
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let account_ids: Vec<Uuid> = placements
+            .iter()
+            .map(|(account_id, _)| *account_id)
+            .collect();
+        // Lock the ratings so that games finishing at the same time do not each update them from
+        // the same starting point, losing one game's changes. Players without a rating yet are
+        // given the default one first, so that there is a row to lock.
+        let initial = Rating::default();
+        let defaults: Vec<_> = account_ids
+            .iter()
+            .map(|account_id| {
+                (
+                    player_ratings::universe_id.eq(game.universe_id),
+                    player_ratings::account_id.eq(*account_id),
+                    player_ratings::rating.eq(initial.rating),
+                    player_ratings::deviation.eq(initial.deviation),
+                    player_ratings::volatility.eq(initial.volatility),
+                    player_ratings::games_played.eq(0),
+                )
+            })
+            .collect();
+        insert_into(player_ratings::table)
+            .values(&defaults)
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        let existing: HashMap<Uuid, Rating> = player_ratings::table
+            .filter(player_ratings::universe_id.eq(game.universe_id))
+            .filter(player_ratings::account_id.eq_any(&account_ids))
+            .order_by(player_ratings::account_id)
+            .for_update()
+            .load::<PlayerRating>(conn)?
+            .into_iter()
+            .map(|rating| {
+                (
+                    rating.account_id,
+                    Rating {
+                        rating: rating.rating,
+                        deviation: rating.deviation,
+                        volatility: rating.volatility,
+                    },
+                )
+            })
+            .collect();
+        let rating_of = |account_id: &Uuid| existing.get(account_id).copied().unwrap_or_default();
+
+        for (account_id, placement) in placements {
+            let outcomes: Vec<(Rating, f64)> = placements
+                .iter()
+                .filter(|(opponent_id, _)| opponent_id != account_id)
+                .map(|(opponent_id, opponent_placement)| {
+                    let score = match placement.cmp(opponent_placement) {
+                        std::cmp::Ordering::Less => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Greater => 0.0,
+                    };
+                    (rating_of(opponent_id), score)
+                })
+                .collect();
+            let rating = rating_of(account_id).update(&outcomes);
+            insert_into(player_ratings::table)
+                .values((
+                    player_ratings::universe_id.eq(game.universe_id),
+                    player_ratings::account_id.eq(account_id),
+                    player_ratings::rating.eq(rating.rating),
+                    player_ratings::deviation.eq(rating.deviation),
+                    player_ratings::volatility.eq(rating.volatility),
+                    player_ratings::games_played.eq(1),
+                ))
+                .on_conflict((player_ratings::universe_id, player_ratings::account_id))
+                .do_update()
+                .set((
+                    player_ratings::rating.eq(rating.rating),
+                    player_ratings::deviation.eq(rating.deviation),
+                    player_ratings::volatility.eq(rating.volatility),
+                    player_ratings::games_played.eq(player_ratings::games_played + 1),
+                    player_ratings::updated_at.eq(now),
+                ))
+                .execute(conn)?;
+        }
+        Ok(())
+    }
+}
@@ -52,4 +52,25 @@ impl Mutation {
                 Ok(universe_version)
             })
     }
+
+    pub fn released_universe_version(
+        &self,
+        universe_id: Uuid,
+        version: i32,
+        conn: &DbConnection,
+    ) -> anyhow::Result<UniverseVersion> {
+        universe_versions::table
+            .filter(universe_versions::universe_id.eq(universe_id))
+            .filter(universe_versions::version.eq(version))
+            .filter(universe_versions::released_at.is_not_null())
+            .get_result(conn)
+            .optional()?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "This version ({}) of the universe ({}) does not exist, or has not been released",
+                    version,
+                    universe_id,
+                )
+            })
+    }
 }
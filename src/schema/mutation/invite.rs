@@ -0,0 +1,303 @@
+use super::{Context, Invite, Mutation};
+use crate::jwt;
+use chrono::{Duration, Utc};
+use data::*;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+/// How long an invite may be redeemed for, if no expiry is chosen.
+const DEFAULT_INVITE_HOURS: i32 = 24 * 7;
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct CreateGameInvite {
+    game: Uuid,
+    expires_in_hours: Option<i32>,
+    max_uses: Option<i32>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct CreateUniverseInvite {
+    universe: Uuid,
+    role: ContributorRole,
+    expires_in_hours: Option<i32>,
+    max_uses: Option<i32>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RedeemInvite {
+    token: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RevokeInvite {
+    id: Uuid,
+}
+
+impl Mutation {
+    pub(super) fn create_game_invite(
+        &self,
+        context: &Context,
+        CreateGameInvite {
+            game,
+            expires_in_hours,
+            max_uses,
+        }: CreateGameInvite,
+    ) -> anyhow::Result<Invite> {
+        let account_id = context.try_authenticated_account()?;
+        let invite = context.transaction(|conn| {
+            self.assert_game_host(game, account_id, conn)?;
+            let game: data::Game = games::table.find(game).get_result(conn)?;
+            anyhow::ensure!(
+                game.started_at.is_none(),
+                "This game ({}) has already started",
+                game.id,
+            );
+            let invite: data::Invite = insert_into(invites::table)
+                .values((
+                    invites::game_id.eq(game.id),
+                    invites::created_by.eq(account_id),
+                    invites::expires_at.eq(self.invite_expiry(expires_in_hours)?),
+                    invites::max_uses.eq(self.validate_max_uses(max_uses)?),
+                ))
+                .returning(invites::all_columns)
+                .get_result(conn)?;
+            Ok(invite)
+        })?;
+
+        let query = Invite::new(invite.id);
+        context.invites().prime(invite);
+        Ok(query)
+    }
+
+    pub(super) fn create_universe_invite(
+        &self,
+        context: &Context,
+        CreateUniverseInvite {
+            universe,
+            role,
+            expires_in_hours,
+            max_uses,
+        }: CreateUniverseInvite,
+    ) -> anyhow::Result<Invite> {
+        let account_id = context.try_authenticated_account()?;
+        let invite = context.transaction(|conn| {
+            self.assert_universe_owner(universe, account_id, conn)?;
+            anyhow::ensure!(
+                [
+                    ContributorRole::Maintainer,
+                    ContributorRole::Contributor,
+                    ContributorRole::Reviewer,
+                    ContributorRole::Viewer,
+                ]
+                .contains(&role),
+                "A contributor cannot be invited with this role ({:?})",
+                role,
+            );
+            let invite: data::Invite = insert_into(invites::table)
+                .values((
+                    invites::universe_id.eq(universe),
+                    invites::role.eq(role),
+                    invites::created_by.eq(account_id),
+                    invites::expires_at.eq(self.invite_expiry(expires_in_hours)?),
+                    invites::max_uses.eq(self.validate_max_uses(max_uses)?),
+                ))
+                .returning(invites::all_columns)
+                .get_result(conn)?;
+            Ok(invite)
+        })?;
+
+        let query = Invite::new(invite.id);
+        context.invites().prime(invite);
+        Ok(query)
+    }
+
+    pub(super) fn redeem_invite(
+        &self,
+        context: &Context,
+        RedeemInvite { token }: RedeemInvite,
+    ) -> anyhow::Result<Invite> {
+        let account_id = context.try_authenticated_account()?;
+        let invite_id = jwt::decode_invite(&token)
+            .map_err(|_| anyhow::anyhow!("This invite is not valid, or has expired"))?;
+        let invite = context.transaction(|conn| {
+            let invite: data::Invite = invites::table
+                .find(invite_id)
+                .for_update()
+                .get_result(conn)?;
+            anyhow::ensure!(
+                invite.revoked_at.is_none(),
+                "This invite ({}) has been revoked",
+                invite.id,
+            );
+            anyhow::ensure!(
+                invite.expires_at > Utc::now(),
+                "This invite ({}) has expired",
+                invite.id,
+            );
+            anyhow::ensure!(
+                invite.max_uses.map(|max| invite.uses < max).unwrap_or(true),
+                "This invite ({}) has already been used",
+                invite.id,
+            );
+
+            if let Some(game_id) = invite.game_id {
+                self.redeem_game_invite(&invite, game_id, account_id, conn)?;
+            }
+            if let (Some(universe_id), Some(role)) = (invite.universe_id, invite.role) {
+                self.redeem_universe_invite(&invite, universe_id, role, account_id, conn)?;
+            }
+
+            let invite = update(&invite)
+                .set(invites::uses.eq(invites::uses + 1))
+                .returning(invites::all_columns)
+                .get_result(conn)?;
+            Ok(invite)
+        })?;
+
+        let query = Invite::new(invite.id);
+        context.invites().prime(invite);
+        Ok(query)
+    }
+
+    pub(super) fn revoke_invite(
+        &self,
+        context: &Context,
+        RevokeInvite { id }: RevokeInvite,
+    ) -> anyhow::Result<Invite> {
+        let account_id = context.try_authenticated_account()?;
+        let invite = context.transaction(|conn| {
+            let invite: data::Invite = invites::table.find(id).get_result(conn)?;
+            anyhow::ensure!(
+                invite.created_by == account_id,
+                "Only the issuer of this invite ({}) may revoke it",
+                id,
+            );
+            let invite = update(&invite)
+                .set(invites::revoked_at.eq(now))
+                .returning(invites::all_columns)
+                .get_result(conn)?;
+            Ok(invite)
+        })?;
+
+        let query = Invite::new(invite.id);
+        context.invites().prime(invite);
+        Ok(query)
+    }
+
+    /// Seats the account in a game which has not yet started, taking an open seat if there is one,
+    /// or otherwise adding a seat if the game's map allows it. The issuer must still be hosting
+    /// the game.
+    fn redeem_game_invite(
+        &self,
+        invite: &data::Invite,
+        game_id: Uuid,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        let game: data::Game = games::table.find(game_id).for_update().get_result(conn)?;
+        self.assert_game_host(game_id, invite.created_by, conn)?;
+        anyhow::ensure!(
+            game.started_at.is_none(),
+            "This game ({}) has already started",
+            game_id,
+        );
+        self.assert_universe_visible(game.universe_id, account_id, conn)?;
+        let player: Option<data::Player> = players::table
+            .filter(players::game_id.eq(game_id))
+            .filter(players::account_id.eq(account_id))
+            .get_result(conn)
+            .optional()?;
+        if let Some(player) = &player {
+            if player.engagement == PlayerEngagement::Pending {
+                update(player)
+                    .set(players::engagement.eq(PlayerEngagement::Player))
+                    .execute(conn)?;
+                return self.start_game_if_full(game_id, conn);
+            }
+            anyhow::ensure!(
+                player.engagement == PlayerEngagement::Declined,
+                "You are already in this game ({})",
+                game_id,
+            );
+        }
+        if self.game_open_seats(&game, conn)? <= 0 {
+            self.assert_seats_within_map(&game, game.seats + 1, conn)?;
+            update(&game)
+                .set(games::seats.eq(games::seats + 1))
+                .execute(conn)?;
+        }
+        let last_turn: Option<i32> = players::table
+            .select(max(players::turn_order))
+            .filter(players::game_id.eq(game_id))
+            .get_result(conn)?;
+        let turn_order = last_turn.map(|turn| turn + 1).unwrap_or(0);
+        insert_into(players::table)
+            .values((
+                players::game_id.eq(game_id),
+                players::account_id.eq(account_id),
+                players::turn_order.eq(turn_order),
+                players::engagement.eq(PlayerEngagement::Player),
+            ))
+            .on_conflict((players::game_id, players::account_id))
+            .do_update()
+            .set((
+                players::turn_order.eq(turn_order),
+                players::engagement.eq(PlayerEngagement::Player),
+            ))
+            .execute(conn)?;
+        self.start_game_if_full(game_id, conn)
+    }
+
+    /// Makes the account a contributor to a universe with the role offered by the invite. The
+    /// issuer must still own the universe, and the change is recorded as made by them.
+    fn redeem_universe_invite(
+        &self,
+        invite: &data::Invite,
+        universe_id: Uuid,
+        role: ContributorRole,
+        account_id: Uuid,
+        conn: &DbConnection,
+    ) -> anyhow::Result<()> {
+        self.assert_universe_owner(universe_id, invite.created_by, conn)?;
+        let existing_contributor = contributors::table
+            .filter(contributors::account_id.eq(account_id))
+            .filter(contributors::universe_id.eq(universe_id))
+            .filter(contributors::role.ne(ContributorRole::Declined))
+            .filter(contributors::role.ne(ContributorRole::Pending));
+        let contributor_exists: bool = select(exists(existing_contributor)).get_result(conn)?;
+        anyhow::ensure!(
+            !contributor_exists,
+            "You ({}) are already a contributor to this universe ({})",
+            account_id,
+            universe_id,
+        );
+        insert_into(contributors::table)
+            .values((
+                contributors::universe_id.eq(universe_id),
+                contributors::account_id.eq(account_id),
+                contributors::role.eq(role),
+            ))
+            .on_conflict((contributors::universe_id, contributors::account_id))
+            .do_update()
+            .set(contributors::role.eq(role))
+            .execute(conn)?;
+        self.record_contributor_change(universe_id, account_id, invite.created_by, Some(role), conn)
+    }
+
+    fn invite_expiry(
+        &self,
+        expires_in_hours: Option<i32>,
+    ) -> anyhow::Result<chrono::DateTime<Utc>> {
+        let hours = expires_in_hours.unwrap_or(DEFAULT_INVITE_HOURS);
+        anyhow::ensure!(hours > 0, "An invite must expire in the future");
+        Ok(Utc::now() + Duration::hours(hours as i64))
+    }
+
+    fn validate_max_uses(&self, max_uses: Option<i32>) -> anyhow::Result<Option<i32>> {
+        if let Some(max_uses) = max_uses {
+            anyhow::ensure!(max_uses > 0, "An invite must allow at least one use");
+        }
+        Ok(max_uses)
+    }
+}
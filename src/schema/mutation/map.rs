@@ -1,4 +1,5 @@
 use super::{Context, MapVersion, Mutation};
+use crate::schema::query;
 use data::*;
 use diesel::dsl::*;
 use diesel::prelude::*;
@@ -8,26 +9,70 @@ use uuid::Uuid;
 pub struct CreateMap {
     name: String,
     universe: Uuid,
+    display_name: Option<String>,
+    description: Option<String>,
+    tags: Option<Vec<String>>,
 }
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct UpdateMap {
     id: Uuid,
     script: String,
+    revision: Option<String>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct UpdateMapDetails {
+    id: Uuid,
+    name: Option<String>,
+    display_name: Option<String>,
+    description: Option<String>,
+    tags: Option<Vec<String>>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct DeleteMap {
+    id: Uuid,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RevertMap {
+    id: Uuid,
+    to_version: i32,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct SetMapAsset {
+    id: Uuid,
+    name: String,
+    asset: Option<String>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct SetMapEditors {
+    id: Uuid,
+    editors: Option<Vec<Uuid>>,
 }
 
 impl Mutation {
     pub(super) fn create_map(
         &self,
         context: &Context,
-        CreateMap { name, universe }: CreateMap,
+        CreateMap {
+            name,
+            universe,
+            display_name,
+            description,
+            tags,
+        }: CreateMap,
     ) -> anyhow::Result<MapVersion> {
         let account_id = context.try_authenticated_account()?;
         let (map, map_version) = context.transaction(|conn| {
             self.assert_universe_contributor(universe, account_id, conn)?;
             let existing = maps::table
                 .filter(maps::universe_id.eq(&universe))
-                .filter(maps::name.eq(&name));
+                .filter(maps::name.eq(&name))
+                .filter(maps::deleted_at.is_null());
             let map_exists: bool = select(exists(existing)).get_result(conn)?;
             anyhow::ensure!(
                 !map_exists,
@@ -35,7 +80,13 @@ impl Mutation {
                 &name,
             );
             let map: data::Map = insert_into(maps::table)
-                .values((maps::name.eq(&name), maps::universe_id.eq(&universe)))
+                .values((
+                    maps::name.eq(&name),
+                    maps::universe_id.eq(&universe),
+                    maps::display_name.eq(&display_name),
+                    maps::description.eq(&description),
+                    maps::tags.eq(tags.unwrap_or_default()),
+                ))
                 .returning(maps::all_columns)
                 .get_result(conn)?;
             let map_version: data::MapVersion = insert_into(map_versions::table)
@@ -55,57 +106,252 @@ impl Mutation {
         Ok(query)
     }
 
-    #[rustfmt::skip]
     pub(super) fn update_map(
         &self,
         context: &Context,
-        UpdateMap { id, script }: UpdateMap,
+        UpdateMap {
+            id,
+            script,
+            revision,
+        }: UpdateMap,
     ) -> anyhow::Result<MapVersion> {
         let account_id = context.try_authenticated_account()?;
         let map_version: data::MapVersion = context.transaction(|conn| {
             let map = maps::table
                 .filter(maps::id.eq(id))
+                .for_update()
                 .get_result::<Map>(conn)?;
-            self.assert_universe_contributor(map.universe_id, account_id, conn)?;
-            let most_recent_version = self.map_current_version(map.id, conn)?;
-            let same_universe_version = universe_versions::universe_id.eq(universe_version_maps::universe_id)
-                .and(universe_versions::version.eq(universe_version_maps::universe_version));
-            let version_in_use = universe_version_maps::table
-                .inner_join(universe_versions::table.on(same_universe_version))
-                .filter(universe_version_maps::map_id.eq(map.id))
-                .filter(universe_version_maps::map_version.eq(most_recent_version))
-                .filter(universe_versions::released_at.is_not_null());
-            let version_in_use = select(exists(version_in_use)).get_result(conn)?;
-            let map_version: data::MapVersion = if version_in_use {
-                insert_into(map_versions::table)
-                    .values((
-                        map_versions::map_id.eq(map.id),
-                        map_versions::version.eq(most_recent_version + 1),
-                        map_versions::script.eq(&script),
-                    ))
-                    .returning(map_versions::all_columns)
-                    .get_result(conn)?
-            } else {
-                update(map_versions::table)
-                    .set(map_versions::script.eq(&script))
-                    .filter(map_versions::map_id.eq(id))
-                    .filter(map_versions::version.eq(most_recent_version))
-                    .returning(map_versions::all_columns)
-                    .get_result(conn)?
-            };
+            self.assert_map_editor(&map, account_id, conn)?;
+            if let Some(revision) = &revision {
+                self.assert_map_revision(map.id, revision, conn)?;
+            }
+            self.write_map_script(&map, &script, conn)
+        })?;
+
+        let query = MapVersion::new(map_version.map_id, map_version.version);
+        context.map_versions().prime(map_version);
+        Ok(query)
+    }
+
+    pub(super) fn update_map_details(
+        &self,
+        context: &Context,
+        UpdateMapDetails {
+            id,
+            name,
+            display_name,
+            description,
+            tags,
+        }: UpdateMapDetails,
+    ) -> anyhow::Result<query::Map> {
+        let account_id = context.try_authenticated_account()?;
+        let map = context.transaction(|conn| {
+            let map = maps::table
+                .filter(maps::id.eq(id))
+                .for_update()
+                .get_result::<Map>(conn)?;
+            anyhow::ensure!(
+                map.deleted_at.is_none(),
+                "This map ({}) has been deleted",
+                id,
+            );
+            self.assert_map_editor(&map, account_id, conn)?;
+            if let Some(name) = name {
+                let existing = maps::table
+                    .filter(maps::universe_id.eq(map.universe_id))
+                    .filter(maps::name.eq(&name))
+                    .filter(maps::id.ne(id))
+                    .filter(maps::deleted_at.is_null());
+                let map_exists: bool = select(exists(existing)).get_result(conn)?;
+                anyhow::ensure!(
+                    !map_exists,
+                    "A map with this name ({}) already exists",
+                    &name,
+                );
+                update(&map).set(maps::name.eq(name)).execute(conn)?;
+            }
+            if let Some(display_name) = display_name {
+                let display_name = Some(display_name).filter(|name| !name.is_empty());
+                update(&map)
+                    .set(maps::display_name.eq(display_name))
+                    .execute(conn)?;
+            }
+            if let Some(description) = description {
+                let description = Some(description).filter(|description| !description.is_empty());
+                update(&map)
+                    .set(maps::description.eq(description))
+                    .execute(conn)?;
+            }
+            if let Some(tags) = tags {
+                update(&map).set(maps::tags.eq(tags)).execute(conn)?;
+            }
+            let map: data::Map = maps::table.find(id).get_result(conn)?;
+            Ok(map)
+        })?;
+
+        let query = query::Map::new(map.id);
+        context.maps().prime(map);
+        Ok(query)
+    }
+
+    pub(super) fn delete_map(
+        &self,
+        context: &Context,
+        DeleteMap { id }: DeleteMap,
+    ) -> anyhow::Result<()> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            let map = maps::table
+                .filter(maps::id.eq(id))
+                .for_update()
+                .get_result::<Map>(conn)?;
+            anyhow::ensure!(
+                map.deleted_at.is_none(),
+                "This map ({}) has already been deleted",
+                id,
+            );
+            self.assert_universe_maintainer(map.universe_id, account_id, conn)?;
+            let proposed_in_open_change_request = change_request_maps::table
+                .inner_join(change_requests::table)
+                .filter(change_request_maps::map_id.eq(id))
+                .filter(change_requests::state.eq(ChangeRequestState::Open));
+            let proposed_in_open_change_request: bool =
+                select(exists(proposed_in_open_change_request)).get_result(conn)?;
+            anyhow::ensure!(
+                !proposed_in_open_change_request,
+                "This map ({}) has changes proposed in an open change request, which must be closed before it can be deleted",
+                id,
+            );
             let universe_version = self.unreleased_universe_version(map.universe_id, conn)?;
-            update(universe_version_maps::table)
+            let unreleased_pin = universe_version_maps::table
                 .filter(universe_version_maps::universe_id.eq(universe_version.universe_id))
                 .filter(universe_version_maps::universe_version.eq(universe_version.version))
-                .filter(universe_version_maps::map_id.eq(map_version.map_id))
-                .set(universe_version_maps::map_version.eq(map_version.version))
+                .filter(universe_version_maps::map_id.eq(id));
+            delete(unreleased_pin).execute(conn)?;
+            delete(change_request_maps::table.filter(change_request_maps::map_id.eq(id)))
                 .execute(conn)?;
+
+            if self.map_released(id, conn)? {
+                // Released versions of the universe still use this map, so it must be kept.
+                update(&map).set(maps::deleted_at.eq(now)).execute(conn)?;
+            } else {
+                delete(map_editors::table.filter(map_editors::map_id.eq(id))).execute(conn)?;
+                delete(universe_version_maps::table.filter(universe_version_maps::map_id.eq(id)))
+                    .execute(conn)?;
+                delete(map_version_assets::table.filter(map_version_assets::map_id.eq(id)))
+                    .execute(conn)?;
+                delete(map_versions::table.filter(map_versions::map_id.eq(id))).execute(conn)?;
+                delete(&map).execute(conn)?;
+            }
+            Ok(())
+        })
+    }
+
+    pub(super) fn revert_map(
+        &self,
+        context: &Context,
+        RevertMap { id, to_version }: RevertMap,
+    ) -> anyhow::Result<MapVersion> {
+        let account_id = context.try_authenticated_account()?;
+        let map_version: data::MapVersion = context.transaction(|conn| {
+            let map = maps::table
+                .filter(maps::id.eq(id))
+                .for_update()
+                .get_result::<Map>(conn)?;
+            self.assert_map_editor(&map, account_id, conn)?;
+            let script: String = map_versions::table
+                .select(map_versions::script)
+                .filter(map_versions::map_id.eq(id))
+                .filter(map_versions::version.eq(to_version))
+                .get_result(conn)
+                .optional()?
+                .ok_or_else(|| {
+                    anyhow::anyhow!("This map ({}) has no version {}", id, to_version)
+                })?;
+            self.write_map_script(&map, &script, conn)
+        })?;
+
+        let query = MapVersion::new(map_version.map_id, map_version.version);
+        context.map_versions().prime(map_version);
+        Ok(query)
+    }
+
+    pub(super) fn set_map_asset(
+        &self,
+        context: &Context,
+        SetMapAsset { id, name, asset }: SetMapAsset,
+    ) -> anyhow::Result<MapVersion> {
+        let account_id = context.try_authenticated_account()?;
+        let map_version: data::MapVersion = context.transaction(|conn| {
+            let map = maps::table
+                .filter(maps::id.eq(id))
+                .for_update()
+                .get_result::<Map>(conn)?;
+            self.assert_map_editor(&map, account_id, conn)?;
+            if let Some(hash) = &asset {
+                let asset_exists: bool =
+                    select(exists(assets::table.find(hash))).get_result(conn)?;
+                anyhow::ensure!(asset_exists, "This asset ({}) has not been uploaded", hash);
+            }
+            // Rewriting the current script moves to a new version if the current one has been
+            // released, so that released versions keep the assets they were released with.
+            let most_recent_version = self.map_current_version(id, conn)?;
+            let script: String = map_versions::table
+                .find((id, most_recent_version))
+                .select(map_versions::script)
+                .get_result(conn)?;
+            let map_version = self.write_map_script(&map, &script, conn)?;
+
+            let existing = map_version_assets::table
+                .filter(map_version_assets::map_id.eq(id))
+                .filter(map_version_assets::map_version.eq(map_version.version))
+                .filter(map_version_assets::name.eq(&name));
+            delete(existing).execute(conn)?;
+            if let Some(hash) = asset {
+                insert_into(map_version_assets::table)
+                    .values((
+                        map_version_assets::map_id.eq(id),
+                        map_version_assets::map_version.eq(map_version.version),
+                        map_version_assets::name.eq(&name),
+                        map_version_assets::asset_hash.eq(hash),
+                    ))
+                    .execute(conn)?;
+            }
             Ok(map_version)
         })?;
 
-        let query =
-            MapVersion::new(map_version.map_id, map_version.version);
+        let query = MapVersion::new(map_version.map_id, map_version.version);
         context.map_versions().prime(map_version);
         Ok(query)
     }
+
+    pub(super) fn set_map_editors(
+        &self,
+        context: &Context,
+        SetMapEditors { id, editors }: SetMapEditors,
+    ) -> anyhow::Result<query::Map> {
+        let account_id = context.try_authenticated_account()?;
+        let map = context.transaction(|conn| {
+            let map = maps::table
+                .filter(maps::id.eq(id))
+                .get_result::<Map>(conn)?;
+            self.assert_universe_maintainer(map.universe_id, account_id, conn)?;
+            delete(map_editors::table.filter(map_editors::map_id.eq(id))).execute(conn)?;
+            for editor in editors.unwrap_or_default() {
+                self.assert_universe_contributor(map.universe_id, editor, conn)?;
+                insert_into(map_editors::table)
+                    .values((
+                        map_editors::map_id.eq(id),
+                        map_editors::account_id.eq(editor),
+                    ))
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+            }
+            Ok(map)
+        })?;
+
+        let query = query::Map::new(map.id);
+        context.maps().prime(map);
+        Ok(query)
+    }
 }
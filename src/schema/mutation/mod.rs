@@ -6,10 +6,15 @@ mod helpers;
 mod account;
 mod archetype;
 mod auth;
+mod change_request;
 mod contributor;
 mod email;
+mod friend;
 mod game;
+mod game_migration;
+mod invite;
 mod map;
+mod notification;
 mod universe;
 
 pub struct Mutation;
@@ -63,9 +68,84 @@ impl Mutation {
         self.verify_email(context, email).into()
     }
 
+    /// Mark notifications in your inbox as read. If no notifications are specified, every unread
+    /// notification is marked.
+    fn mark_notifications_read(
+        &self,
+        context: &Context,
+        notifications: notification::MarkNotificationsRead,
+    ) -> OperationResult<bool> {
+        self.mark_notifications_read(context, notifications)
+            .map(|()| true)
+            .into()
+    }
+
+    // -- Friends --
+
+    /// Ask another account to be your friend.
+    fn send_friend_request(
+        &self,
+        context: &Context,
+        request: friend::SendFriendRequest,
+    ) -> OperationResult<bool> {
+        self.send_friend_request(context, request)
+            .map(|()| true)
+            .into()
+    }
+
+    /// Accept a friend request sent to you.
+    fn accept_friend_request(
+        &self,
+        context: &Context,
+        request: friend::FriendRequest,
+    ) -> OperationResult<bool> {
+        self.respond_to_friend_request(context, request, true)
+            .map(|()| true)
+            .into()
+    }
+
+    /// Decline a friend request sent to you.
+    fn decline_friend_request(
+        &self,
+        context: &Context,
+        request: friend::FriendRequest,
+    ) -> OperationResult<bool> {
+        self.respond_to_friend_request(context, request, false)
+            .map(|()| true)
+            .into()
+    }
+
+    /// Stop being friends with an account, or cancel a friend request you sent it.
+    fn remove_friend(
+        &self,
+        context: &Context,
+        friend: friend::RemoveFriend,
+    ) -> OperationResult<bool> {
+        self.remove_friend(context, friend).map(|()| true).into()
+    }
+
+    /// Block an account. Blocked accounts cannot invite you to games or universes, or send you
+    /// friend requests, and any friendship with them ends.
+    fn block_account(
+        &self,
+        context: &Context,
+        account: friend::BlockAccount,
+    ) -> OperationResult<bool> {
+        self.block_account(context, account).map(|()| true).into()
+    }
+
+    /// Unblock an account you have blocked.
+    fn unblock_account(
+        &self,
+        context: &Context,
+        account: friend::BlockAccount,
+    ) -> OperationResult<bool> {
+        self.unblock_account(context, account).map(|()| true).into()
+    }
+
     // -- Universes --
 
-    /// Create a new universe.
+    /// Create a new universe. Universes are private unless another visibility is chosen.
     fn create_universe(
         &self,
         context: &Context,
@@ -83,7 +163,38 @@ impl Mutation {
         self.update_universe(context, universe).into()
     }
 
-    /// Release the current unreleased version of a universe. If no unreleased version exists, this will fail.
+    /// Choose who may find and see a universe you own. Private universes are visible only to
+    /// their contributors, unlisted universes to anyone who knows their ID, and public universes
+    /// appear in search.
+    fn set_universe_visibility(
+        &self,
+        context: &Context,
+        universe: universe::SetUniverseVisibility,
+    ) -> OperationResult<Universe> {
+        self.set_universe_visibility(context, universe).into()
+    }
+
+    /// Add a universe to, or remove it from, your favorites.
+    fn favorite_universe(
+        &self,
+        context: &Context,
+        universe: universe::FavoriteUniverse,
+    ) -> OperationResult<Universe> {
+        self.favorite_universe(context, universe).into()
+    }
+
+    /// Rate a universe from 1 to 5, replacing any rating you gave it before. If no rating is
+    /// provided, your rating is removed.
+    fn rate_universe(
+        &self,
+        context: &Context,
+        universe: universe::RateUniverse,
+    ) -> OperationResult<Universe> {
+        self.rate_universe(context, universe).into()
+    }
+
+    /// Release the current unreleased version of a universe. If no unreleased version exists, or
+    /// the version does not pass validation, this will fail.
     fn publish_universe(
         &self,
         context: &Context,
@@ -92,6 +203,26 @@ impl Mutation {
         self.publish_universe(context, universe).into()
     }
 
+    /// Withdraw a released version of a universe. New games will no longer be created with a
+    /// yanked version, but games already using it are unaffected.
+    fn yank_universe_version(
+        &self,
+        context: &Context,
+        universe: universe::YankUniverseVersion,
+    ) -> OperationResult<UniverseVersion> {
+        self.yank_universe_version(context, universe).into()
+    }
+
+    /// Mark a released version of a universe as deprecated, discouraging its use without
+    /// withdrawing it.
+    fn deprecate_universe_version(
+        &self,
+        context: &Context,
+        universe: universe::DeprecateUniverseVersion,
+    ) -> OperationResult<UniverseVersion> {
+        self.deprecate_universe_version(context, universe).into()
+    }
+
     // -- Contributors --
 
     /// Invite a person to be a contributor to a universe you own.
@@ -123,6 +254,55 @@ impl Mutation {
             .into()
     }
 
+    /// Cancel an invitation you have sent for a person to contribute to a universe you own.
+    fn cancel_contributor_invitation(
+        &self,
+        context: &Context,
+        invitation: contributor::CancelContributorInvitation,
+    ) -> OperationResult<bool> {
+        self.cancel_contributor_invitation(context, invitation)
+            .map(|()| true)
+            .into()
+    }
+
+    /// Remove a contributor from a universe you own.
+    fn remove_contributor(
+        &self,
+        context: &Context,
+        contributor: contributor::RemoveContributor,
+    ) -> OperationResult<bool> {
+        self.remove_contributor(context, contributor)
+            .map(|()| true)
+            .into()
+    }
+
+    /// Stop contributing to a universe. The owner must transfer ownership before leaving.
+    fn leave_universe(
+        &self,
+        context: &Context,
+        universe: contributor::LeaveUniverse,
+    ) -> OperationResult<bool> {
+        self.leave_universe(context, universe).map(|()| true).into()
+    }
+
+    /// Change the role of a contributor to a universe you own.
+    fn set_contributor_role(
+        &self,
+        context: &Context,
+        contributor: contributor::SetContributorRole,
+    ) -> OperationResult<Contributor> {
+        self.set_contributor_role(context, contributor).into()
+    }
+
+    /// Make another contributor the owner of a universe you own. You remain a contributor.
+    fn transfer_universe_ownership(
+        &self,
+        context: &Context,
+        transfer: contributor::TransferUniverseOwnership,
+    ) -> OperationResult<Contributor> {
+        self.transfer_universe_ownership(context, transfer).into()
+    }
+
     // -- Archetypes --
 
     /// Create a new archetype.
@@ -134,7 +314,8 @@ impl Mutation {
         self.create_archetype(context, archetype).into()
     }
 
-    /// Update an existing archetype.
+    /// Update an existing archetype. If a revision is provided, the update is rejected if the
+    /// archetype has been changed since that revision.
     fn update_archetype(
         &self,
         context: &Context,
@@ -143,6 +324,60 @@ impl Mutation {
         self.update_archetype(context, archetype).into()
     }
 
+    /// Rename an archetype, or change its display name, description or tags. An empty display
+    /// name or description clears it.
+    fn update_archetype_details(
+        &self,
+        context: &Context,
+        archetype: archetype::UpdateArchetypeDetails,
+    ) -> OperationResult<Archetype> {
+        self.update_archetype_details(context, archetype).into()
+    }
+
+    /// Delete an archetype. If it is used by a released version of its universe it is only marked
+    /// as deleted, and remains available to those versions. Only maintainers may delete
+    /// archetypes, and not while an open change request proposes changes to them.
+    fn delete_archetype(
+        &self,
+        context: &Context,
+        archetype: archetype::DeleteArchetype,
+    ) -> OperationResult<bool> {
+        self.delete_archetype(context, archetype)
+            .map(|()| true)
+            .into()
+    }
+
+    /// Restore the script of a previous version of an archetype. The script is written as if it
+    /// had been updated, and the archetype is repinned in the unreleased version of its universe.
+    fn revert_archetype(
+        &self,
+        context: &Context,
+        archetype: archetype::RevertArchetype,
+    ) -> OperationResult<ArchetypeVersion> {
+        self.revert_archetype(context, archetype).into()
+    }
+
+    /// Attach an uploaded asset to an archetype under a name such as "sprite" or "icon", replacing
+    /// any asset previously attached under that name. If no asset is provided, the asset attached
+    /// under that name is removed.
+    fn set_archetype_asset(
+        &self,
+        context: &Context,
+        archetype: archetype::SetArchetypeAsset,
+    ) -> OperationResult<ArchetypeVersion> {
+        self.set_archetype_asset(context, archetype).into()
+    }
+
+    /// Restrict which contributors may edit an archetype. Maintainers may always edit every
+    /// archetype. If no editors are provided, any contributor may edit it.
+    fn set_archetype_editors(
+        &self,
+        context: &Context,
+        archetype: archetype::SetArchetypeEditors,
+    ) -> OperationResult<Archetype> {
+        self.set_archetype_editors(context, archetype).into()
+    }
+
     // -- Maps --
 
     /// Create a new map.
@@ -150,14 +385,119 @@ impl Mutation {
         self.create_map(context, map).into()
     }
 
-    /// Update an existing map.
+    /// Update an existing map. If a revision is provided, the update is rejected if the map has
+    /// been changed since that revision.
     fn update_map(&self, context: &Context, map: map::UpdateMap) -> OperationResult<MapVersion> {
         self.update_map(context, map).into()
     }
 
+    /// Rename a map, or change its display name, description or tags. An empty display
+    /// name or description clears it.
+    fn update_map_details(
+        &self,
+        context: &Context,
+        map: map::UpdateMapDetails,
+    ) -> OperationResult<Map> {
+        self.update_map_details(context, map).into()
+    }
+
+    /// Delete a map. If it is used by a released version of its universe it is only marked
+    /// as deleted, and remains available to those versions. Only maintainers may delete maps,
+    /// and not while an open change request proposes changes to them.
+    fn delete_map(&self, context: &Context, map: map::DeleteMap) -> OperationResult<bool> {
+        self.delete_map(context, map).map(|()| true).into()
+    }
+
+    /// Restore the script of a previous version of a map. The script is written as if it had been
+    /// updated, and the map is repinned in the unreleased version of its universe.
+    fn revert_map(&self, context: &Context, map: map::RevertMap) -> OperationResult<MapVersion> {
+        self.revert_map(context, map).into()
+    }
+
+    /// Attach an uploaded asset to a map under a name such as "sprite" or "icon", replacing
+    /// any asset previously attached under that name. If no asset is provided, the asset attached
+    /// under that name is removed.
+    fn set_map_asset(
+        &self,
+        context: &Context,
+        map: map::SetMapAsset,
+    ) -> OperationResult<MapVersion> {
+        self.set_map_asset(context, map).into()
+    }
+
+    /// Restrict which contributors may edit a map. Maintainers may always edit every map. If no
+    /// editors are provided, any contributor may edit it.
+    fn set_map_editors(&self, context: &Context, map: map::SetMapEditors) -> OperationResult<Map> {
+        self.set_map_editors(context, map).into()
+    }
+
+    // -- Change requests --
+
+    /// Propose changes to the scripts of a universe you contribute to, for review. Each proposed
+    /// script may give the revision it is based on, which defaults to the current revision.
+    fn create_change_request(
+        &self,
+        context: &Context,
+        change_request: change_request::CreateChangeRequest,
+    ) -> OperationResult<ChangeRequest> {
+        self.create_change_request(context, change_request).into()
+    }
+
+    /// Update an open change request you authored. Changing the proposed scripts discards any
+    /// approvals it has received.
+    fn update_change_request(
+        &self,
+        context: &Context,
+        change_request: change_request::UpdateChangeRequest,
+    ) -> OperationResult<ChangeRequest> {
+        self.update_change_request(context, change_request).into()
+    }
+
+    /// Comment on a change request to a universe you contribute to.
+    fn comment_on_change_request(
+        &self,
+        context: &Context,
+        comment: change_request::CommentOnChangeRequest,
+    ) -> OperationResult<ChangeRequestComment> {
+        self.comment_on_change_request(context, comment).into()
+    }
+
+    /// Approve another contributor's open change request.
+    fn approve_change_request(
+        &self,
+        context: &Context,
+        change_request: change_request::ReviewChangeRequest,
+    ) -> OperationResult<ChangeRequest> {
+        self.approve_change_request(context, change_request).into()
+    }
+
+    /// Apply an approved change request to the universe. Only maintainers may merge change
+    /// requests. A change request cannot be merged while any of its proposed scripts conflict
+    /// with changes made since they were proposed.
+    fn merge_change_request(
+        &self,
+        context: &Context,
+        change_request: change_request::ReviewChangeRequest,
+    ) -> OperationResult<ChangeRequest> {
+        self.merge_change_request(context, change_request).into()
+    }
+
+    /// Close a change request without applying it. Only its author or a maintainer may close it.
+    fn close_change_request(
+        &self,
+        context: &Context,
+        change_request: change_request::ReviewChangeRequest,
+    ) -> OperationResult<ChangeRequest> {
+        self.close_change_request(context, change_request).into()
+    }
+
     // -- Games --
 
-    /// Create a new game and invite players to it.
+    /// Create a new game and invite players to it. Open seats may be left for anyone to join. The
+    /// game starts once every seat has been filled by a player. A time control may limit how long
+    /// each turn may take, in seconds, and how a player who runs out of time is penalized. The
+    /// departure rule decides what becomes of a player's entities when they leave the game, and
+    /// defaults to leaving them in play without an owner.
     fn create_game(&self, context: &Context, game: game::CreateGame) -> OperationResult<Game> {
         self.create_game(context, game).into()
     }
@@ -179,4 +519,118 @@ impl Mutation {
     ) -> OperationResult<Game> {
         self.respond_to_game_invitation(context, game, false).into()
     }
+
+    /// Take an open seat in a game that has not yet started. If you were invited to the game, this
+    /// accepts the invitation.
+    fn join_game(&self, context: &Context, game: game::JoinGame) -> OperationResult<Game> {
+        self.join_game(context, game).into()
+    }
+
+    /// Give up your seat in a game that has not yet started. The seat is opened for someone else.
+    fn leave_lobby(&self, context: &Context, game: game::LeaveLobby) -> OperationResult<Game> {
+        self.leave_lobby(context, game).into()
+    }
+
+    /// Invite another player to a game you host which has not yet started. A seat is added for
+    /// them, up to the number of players the game's map is designed for.
+    fn invite_to_game(
+        &self,
+        context: &Context,
+        invitation: game::InviteToGame,
+    ) -> OperationResult<Game> {
+        self.invite_to_game(context, invitation).into()
+    }
+
+    /// Replace a player who has not accepted their invitation to a game you host with someone
+    /// else, who takes their place in the turn order.
+    fn replace_player(
+        &self,
+        context: &Context,
+        player: game::ReplacePlayer,
+    ) -> OperationResult<Game> {
+        self.replace_player(context, player).into()
+    }
+
+    /// End your turn in a game, passing play to the next player. Any time taken beyond the turn
+    /// limit is deducted from your time bank. The map's rules then decide whether any players
+    /// have been eliminated, and whether the game has been won.
+    fn end_turn(&self, context: &Context, game: game::EndTurn) -> OperationResult<Game> {
+        self.end_turn(context, game).into()
+    }
+
+    /// Resign from a game you are playing. If only one player remains, the game ends.
+    fn resign_game(&self, context: &Context, game: game::ResignGame) -> OperationResult<Game> {
+        self.resign_game(context, game).into()
+    }
+
+    /// Remove a player from a game you host. Removed players are not rated for the game.
+    fn remove_player(
+        &self,
+        context: &Context,
+        player: game::RemovePlayer,
+    ) -> OperationResult<Game> {
+        self.remove_player(context, player).into()
+    }
+
+    /// Propose that a game you host be migrated to a newer version of its universe. If version
+    /// is not specified, the current release is proposed. The migration happens once every
+    /// player has accepted.
+    fn propose_game_migration(
+        &self,
+        context: &Context,
+        migration: game_migration::ProposeGameMigration,
+    ) -> OperationResult<Game> {
+        self.propose_game_migration(context, migration).into()
+    }
+
+    /// Vote on the migration proposed for a game. Rejecting the migration discards the proposal.
+    fn vote_on_game_migration(
+        &self,
+        context: &Context,
+        vote: game_migration::VoteOnGameMigration,
+    ) -> OperationResult<Game> {
+        self.vote_on_game_migration(context, vote).into()
+    }
+
+    // -- Invites --
+
+    /// Create a link which seats whoever redeems it in a game you host that has not yet started.
+    /// Invites expire after a week unless another expiry is chosen, and may be limited to a
+    /// number of uses.
+    fn create_game_invite(
+        &self,
+        context: &Context,
+        invite: invite::CreateGameInvite,
+    ) -> OperationResult<Invite> {
+        self.create_game_invite(context, invite).into()
+    }
+
+    /// Create a link which makes whoever redeems it a contributor to a universe you own, with the
+    /// chosen role. Invites expire after a week unless another expiry is chosen, and may be
+    /// limited to a number of uses.
+    fn create_universe_invite(
+        &self,
+        context: &Context,
+        invite: invite::CreateUniverseInvite,
+    ) -> OperationResult<Invite> {
+        self.create_universe_invite(context, invite).into()
+    }
+
+    /// Redeem the token of an invite, joining the game or universe it was issued for.
+    fn redeem_invite(
+        &self,
+        context: &Context,
+        invite: invite::RedeemInvite,
+    ) -> OperationResult<Invite> {
+        self.redeem_invite(context, invite).into()
+    }
+
+    /// Revoke an invite you issued, so that it can no longer be redeemed.
+    fn revoke_invite(
+        &self,
+        context: &Context,
+        invite: invite::RevokeInvite,
+    ) -> OperationResult<Invite> {
+        self.revoke_invite(context, invite).into()
+    }
 }
@@ -0,0 +1,34 @@
+use super::{Context, Mutation};
+use data::notifications;
+use diesel::dsl::*;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct MarkNotificationsRead {
+    ids: Option<Vec<Uuid>>,
+}
+
+impl Mutation {
+    pub(super) fn mark_notifications_read(
+        &self,
+        context: &Context,
+        MarkNotificationsRead { ids }: MarkNotificationsRead,
+    ) -> anyhow::Result<()> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            let mut unread = notifications::table
+                .filter(notifications::account_id.eq(account_id))
+                .filter(notifications::read_at.is_null())
+                .into_boxed();
+            if let Some(ids) = ids {
+                unread = unread.filter(notifications::id.eq_any(ids));
+            }
+            let unread_ids: Vec<Uuid> = unread.select(notifications::id).load(conn)?;
+            update(notifications::table.filter(notifications::id.eq_any(unread_ids)))
+                .set(notifications::read_at.eq(now))
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+}
@@ -1,4 +1,5 @@
 use super::{Context, Mutation, UniverseVersion};
+use crate::schema::{query, validation};
 use data::*;
 use diesel::dsl::*;
 use diesel::prelude::*;
@@ -8,6 +9,7 @@ use uuid::Uuid;
 #[derive(juniper::GraphQLInputObject)]
 pub struct CreateUniverse {
     name: String,
+    visibility: Option<UniverseVisibility>,
 }
 
 #[derive(juniper::GraphQLInputObject)]
@@ -19,16 +21,47 @@ pub struct UpdateUniverse {
     remove_maps: Option<Vec<Uuid>>,
 }
 
+#[derive(juniper::GraphQLInputObject)]
+pub struct SetUniverseVisibility {
+    id: Uuid,
+    visibility: UniverseVisibility,
+}
+
 #[derive(juniper::GraphQLInputObject)]
 pub struct PublishUniverse {
     id: Uuid,
+    release_notes: Option<String>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct YankUniverseVersion {
+    id: Uuid,
+    version: i32,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct DeprecateUniverseVersion {
+    id: Uuid,
+    version: i32,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct FavoriteUniverse {
+    id: Uuid,
+    favorite: bool,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RateUniverse {
+    id: Uuid,
+    rating: Option<i32>,
 }
 
 impl Mutation {
     pub(super) fn create_universe(
         &self,
         context: &Context,
-        CreateUniverse { name }: CreateUniverse,
+        CreateUniverse { name, visibility }: CreateUniverse,
     ) -> anyhow::Result<UniverseVersion> {
         let account_id = context.try_authenticated_account()?;
         let (universe, universe_version, contributor) = context.transaction(|conn| {
@@ -42,7 +75,10 @@ impl Mutation {
                 &name,
             );
             let universe: data::Universe = insert_into(universes::table)
-                .values(universes::name.eq(&name))
+                .values((
+                    universes::name.eq(&name),
+                    universes::visibility.eq(visibility.unwrap_or(UniverseVisibility::Private)),
+                ))
                 .returning(universes::all_columns)
                 .get_result(conn)?;
             let universe_version: data::UniverseVersion = insert_into(universe_versions::table)
@@ -60,6 +96,13 @@ impl Mutation {
                 ))
                 .returning(contributors::all_columns)
                 .get_result(conn)?;
+            self.record_contributor_change(
+                universe.id,
+                account_id,
+                account_id,
+                Some(contributor.role),
+                conn,
+            )?;
             Ok((universe, universe_version, contributor))
         })?;
 
@@ -88,6 +131,8 @@ impl Mutation {
             let universe_version = self.unreleased_universe_version(id, conn)?;
             if let Some(add_archetypes) = add_archetypes {
                 for archetype_id in add_archetypes {
+                    let archetype: Archetype = archetypes::table.find(archetype_id).get_result(conn)?;
+                    anyhow::ensure!(archetype.deleted_at.is_none(), "This archetype ({}) has been deleted", archetype_id);
                     let archetype_version = self.archetype_current_version(archetype_id, conn)?;
                     insert_into(universe_version_archetypes::table)
                         .values((
@@ -117,6 +162,8 @@ impl Mutation {
             }
             if let Some(add_maps) = add_maps {
                 for map_id in add_maps {
+                    let map: Map = maps::table.find(map_id).get_result(conn)?;
+                    anyhow::ensure!(map.deleted_at.is_none(), "This map ({}) has been deleted", map_id);
                     let map_version = self.map_current_version(map_id, conn)?;
                     insert_into(universe_version_maps::table)
                         .values((
@@ -152,21 +199,144 @@ impl Mutation {
         Ok(query)
     }
 
+    pub(super) fn set_universe_visibility(
+        &self,
+        context: &Context,
+        SetUniverseVisibility { id, visibility }: SetUniverseVisibility,
+    ) -> anyhow::Result<query::Universe> {
+        let account_id = context.try_authenticated_account()?;
+        let universe = context.transaction(|conn| {
+            self.assert_universe_owner(id, account_id, conn)?;
+            let universe: data::Universe = update(universes::table.find(id))
+                .set(universes::visibility.eq(visibility))
+                .returning(universes::all_columns)
+                .get_result(conn)?;
+            Ok(universe)
+        })?;
+
+        let query = query::Universe::new(universe.id);
+        context.universes().prime(universe);
+        Ok(query)
+    }
+
     pub(super) fn publish_universe(
         &self,
         context: &Context,
-        PublishUniverse { id }: PublishUniverse,
+        PublishUniverse { id, release_notes }: PublishUniverse,
     ) -> anyhow::Result<UniverseVersion> {
         let account_id = context.try_authenticated_account()?;
-        let universe_version = context.transaction(|conn| {
-            self.assert_universe_owner(id, account_id, conn)?;
-            let mut universe_version: data::UniverseVersion = universe_versions::table
+        let (universe_version, scripts) = context.transaction(|conn| {
+            self.assert_universe_maintainer(id, account_id, conn)?;
+            let universe_version: data::UniverseVersion = universe_versions::table
                 .filter(universe_versions::universe_id.eq(id))
                 .filter(universe_versions::released_at.is_null())
                 .get_result(conn)?;
-            universe_version.released_at = update(&universe_version)
-                .set(universe_versions::released_at.eq(now))
-                .returning(universe_versions::released_at)
+            let scripts = validation::UniverseVersionScripts::load(
+                universe_version.universe_id,
+                universe_version.version,
+                conn,
+            )?;
+            Ok((universe_version, scripts))
+        })?;
+        // The scripts are validated outside of the transaction, as running them may take a while.
+        let validation = tokio::task::block_in_place(|| scripts.validate());
+        anyhow::ensure!(
+            validation.problems.is_empty(),
+            "This version ({}) of the universe ({}) is not ready to be published:\n{}",
+            universe_version.version,
+            universe_version.universe_id,
+            validation
+                .problems
+                .iter()
+                .map(|problem| problem.message.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+        let universe_version = context.transaction(|conn| {
+            self.assert_universe_maintainer(id, account_id, conn)?;
+            let universe_version: data::UniverseVersion = universe_versions::table
+                .find((universe_version.universe_id, universe_version.version))
+                .filter(universe_versions::released_at.is_null())
+                .for_update()
+                .get_result(conn)?;
+            let current_scripts = validation::UniverseVersionScripts::load(
+                universe_version.universe_id,
+                universe_version.version,
+                conn,
+            )?;
+            anyhow::ensure!(
+                current_scripts.same_as(&scripts),
+                "This version ({}) of the universe ({}) was changed while it was being validated. Try again.",
+                universe_version.version,
+                universe_version.universe_id,
+            );
+            for (map_id, version, players) in &validation.player_counts {
+                update(map_versions::table.find((*map_id, *version)))
+                    .set((
+                        map_versions::players.eq(players),
+                        map_versions::players_counted_at.eq(now),
+                    ))
+                    .execute(conn)?;
+            }
+            let universe_version = update(&universe_version)
+                .set((
+                    universe_versions::released_at.eq(now),
+                    universe_versions::release_notes.eq(&release_notes),
+                ))
+                .returning(universe_versions::all_columns)
+                .get_result(conn)?;
+            self.notify_universe_released(id, account_id, conn)?;
+            Ok(universe_version)
+        })?;
+        let query = UniverseVersion::new(universe_version.universe_id, universe_version.version);
+        context.universe_versions().prime(universe_version);
+        Ok(query)
+    }
+
+    pub(super) fn yank_universe_version(
+        &self,
+        context: &Context,
+        YankUniverseVersion { id, version }: YankUniverseVersion,
+    ) -> anyhow::Result<UniverseVersion> {
+        let account_id = context.try_authenticated_account()?;
+        let universe_version = context.transaction(|conn| {
+            self.assert_universe_maintainer(id, account_id, conn)?;
+            let universe_version = self.released_universe_version(id, version, conn)?;
+            anyhow::ensure!(
+                universe_version.yanked_at.is_none(),
+                "This version ({}) of the universe ({}) has already been yanked",
+                version,
+                id,
+            );
+            let universe_version = update(&universe_version)
+                .set(universe_versions::yanked_at.eq(now))
+                .returning(universe_versions::all_columns)
+                .get_result(conn)?;
+            Ok(universe_version)
+        })?;
+        let query = UniverseVersion::new(universe_version.universe_id, universe_version.version);
+        context.universe_versions().prime(universe_version);
+        Ok(query)
+    }
+
+    pub(super) fn deprecate_universe_version(
+        &self,
+        context: &Context,
+        DeprecateUniverseVersion { id, version }: DeprecateUniverseVersion,
+    ) -> anyhow::Result<UniverseVersion> {
+        let account_id = context.try_authenticated_account()?;
+        let universe_version = context.transaction(|conn| {
+            self.assert_universe_maintainer(id, account_id, conn)?;
+            let universe_version = self.released_universe_version(id, version, conn)?;
+            anyhow::ensure!(
+                universe_version.deprecated_at.is_none(),
+                "This version ({}) of the universe ({}) has already been deprecated",
+                version,
+                id,
+            );
+            let universe_version = update(&universe_version)
+                .set(universe_versions::deprecated_at.eq(now))
+                .returning(universe_versions::all_columns)
                 .get_result(conn)?;
             Ok(universe_version)
         })?;
@@ -174,4 +344,66 @@ impl Mutation {
         context.universe_versions().prime(universe_version);
         Ok(query)
     }
+
+    pub(super) fn favorite_universe(
+        &self,
+        context: &Context,
+        FavoriteUniverse { id, favorite }: FavoriteUniverse,
+    ) -> anyhow::Result<query::Universe> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            self.assert_universe_visible(id, account_id, conn)?;
+            if favorite {
+                insert_into(universe_favorites::table)
+                    .values((
+                        universe_favorites::universe_id.eq(id),
+                        universe_favorites::account_id.eq(account_id),
+                    ))
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+            } else {
+                delete(universe_favorites::table.find((id, account_id))).execute(conn)?;
+            }
+            Ok(())
+        })?;
+        Ok(query::Universe::new(id))
+    }
+
+    pub(super) fn rate_universe(
+        &self,
+        context: &Context,
+        RateUniverse { id, rating }: RateUniverse,
+    ) -> anyhow::Result<query::Universe> {
+        let account_id = context.try_authenticated_account()?;
+        context.transaction(|conn| {
+            self.assert_universe_visible(id, account_id, conn)?;
+            match rating {
+                Some(rating) => {
+                    anyhow::ensure!(
+                        (1..=5).contains(&rating),
+                        "A rating must be between 1 and 5, not {}",
+                        rating,
+                    );
+                    insert_into(universe_ratings::table)
+                        .values((
+                            universe_ratings::universe_id.eq(id),
+                            universe_ratings::account_id.eq(account_id),
+                            universe_ratings::rating.eq(rating),
+                        ))
+                        .on_conflict((universe_ratings::universe_id, universe_ratings::account_id))
+                        .do_update()
+                        .set((
+                            universe_ratings::rating.eq(rating),
+                            universe_ratings::updated_at.eq(now),
+                        ))
+                        .execute(conn)?;
+                }
+                None => {
+                    delete(universe_ratings::table.find((id, account_id))).execute(conn)?;
+                }
+            }
+            Ok(())
+        })?;
+        Ok(query::Universe::new(id))
+    }
 }
@@ -1,6 +1,10 @@
-use super::{Context, Contributor, Email, Game, OperationResult, Pagination, QueryWrapper};
+use super::{
+    Context, Contributor, Email, Game, Notification, OperationResult, Pagination, PlayerRating,
+    QueryWrapper, Universe,
+};
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
+use data::FriendshipState;
 use juniper::FieldResult;
 use uuid::Uuid;
 
@@ -23,6 +27,15 @@ impl Account {
     pub fn new(id: Uuid) -> Self {
         Self { id }
     }
+
+    fn assert_owner(&self, context: &Context) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            context.authenticated_account() == Some(self.id),
+            "Only the owner of this account ({}) may see this",
+            self.id,
+        );
+        Ok(())
+    }
 }
 
 #[juniper::graphql_object(Context = Context)]
@@ -82,6 +95,83 @@ impl Account {
             .map(|game| Game::new(game.id));
         Ok(Pagination::new(search, items))
     }
+
+    /// The accounts this account is friends with.
+    fn friends(&self, context: &Context) -> FieldResult<Vec<Account>> {
+        let requested = context
+            .friendships()
+            .for_requester(&self.id)
+            .into_iter()
+            .filter(|friendship| friendship.state == FriendshipState::Accepted)
+            .map(|friendship| friendship.addressee_id);
+        let received = context
+            .friendships()
+            .for_addressee(&self.id)
+            .into_iter()
+            .filter(|friendship| friendship.state == FriendshipState::Accepted)
+            .map(|friendship| friendship.requester_id);
+        Ok(requested.chain(received).map(Account::new).collect())
+    }
+
+    /// The accounts which have sent this account a friend request it has not yet answered. Only
+    /// the account's owner may see these.
+    fn friend_requests(&self, context: &Context) -> FieldResult<Vec<Account>> {
+        self.assert_owner(context)?;
+        Ok(context
+            .friendships()
+            .for_addressee(&self.id)
+            .into_iter()
+            .filter(|friendship| friendship.state == FriendshipState::Pending)
+            .map(|friendship| Account::new(friendship.requester_id))
+            .collect())
+    }
+
+    /// The accounts this account has blocked. Only the account's owner may see these.
+    fn blocked_accounts(&self, context: &Context) -> FieldResult<Vec<Account>> {
+        self.assert_owner(context)?;
+        Ok(context
+            .blocks()
+            .for_account(&self.id)
+            .into_iter()
+            .map(|block| Account::new(block.blocked_id))
+            .collect())
+    }
+
+    /// The notifications in this account's inbox, newest first. Only the account's owner may see
+    /// its notifications.
+    fn notifications(
+        &self,
+        context: &Context,
+        search: Option<data::NotificationSearch>,
+    ) -> FieldResult<Pagination<Notification>> {
+        self.assert_owner(context)?;
+        let search = search.unwrap_or_default().for_account(self.id);
+        let items = context
+            .notifications()
+            .search(&search)?
+            .into_iter()
+            .map(|notification| Notification::new(notification.id));
+        Ok(Pagination::new(search, items))
+    }
+
+    /// The number of unread notifications in this account's inbox. Only the account's owner may
+    /// see this.
+    fn unread_notification_count(&self, context: &Context) -> FieldResult<i32> {
+        self.assert_owner(context)?;
+        Ok(context.notifications().count_unread(self.id)?)
+    }
+
+    /// This account's rating in each universe it has finished a game in, highest first. Only
+    /// universes you can see are included.
+    fn ratings(&self, context: &Context) -> FieldResult<Vec<PlayerRating>> {
+        Ok(context
+            .player_ratings()
+            .for_account(self.load(context)?.id)?
+            .into_iter()
+            .filter(|rating| Universe::new(rating.universe_id).load(context).is_ok())
+            .map(|rating| PlayerRating::new(rating.universe_id, rating.account_id))
+            .collect())
+    }
 }
 
 #[juniper::graphql_object(Context = Context, name = "AccountPagination")]
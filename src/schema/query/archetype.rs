@@ -1,4 +1,4 @@
-use super::{ArchetypeVersion, Context, OperationResult, QueryWrapper};
+use super::{ArchetypeVersion, Context, Contributor, OperationResult, QueryWrapper, Universe};
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
 use juniper::FieldResult;
@@ -12,10 +12,12 @@ impl QueryWrapper for Archetype {
     type Model = data::Archetype;
 
     fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
-        context
+        let archetype = context
             .archetypes()
             .load(self.id)
-            .ok_or_else(|| anyhow!("Archetype {} does not exist", self.id))
+            .ok_or_else(|| anyhow!("Archetype {} does not exist", self.id))?;
+        Universe::new(archetype.universe_id).load(context)?;
+        Ok(archetype)
     }
 }
 
@@ -37,6 +39,27 @@ impl Archetype {
         Ok(self.load(context)?.name.to_owned())
     }
 
+    /// The name of the archetype to show to players.
+    fn display_name(&self, context: &Context) -> FieldResult<Option<String>> {
+        Ok(self.load(context)?.display_name)
+    }
+
+    /// A description of the archetype.
+    fn description(&self, context: &Context) -> FieldResult<Option<String>> {
+        Ok(self.load(context)?.description)
+    }
+
+    /// Tags used to categorize the archetype.
+    fn tags(&self, context: &Context) -> FieldResult<Vec<String>> {
+        Ok(self.load(context)?.tags)
+    }
+
+    /// When this archetype was deleted. Deleted archetypes are kept while released versions of their
+    /// universe still use them.
+    fn deleted_at(&self, context: &Context) -> FieldResult<Option<DateTime<Utc>>> {
+        Ok(self.load(context)?.deleted_at)
+    }
+
     /// When this archetype was created.
     fn created_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
         Ok(self.load(context)?.created_at)
@@ -50,6 +73,18 @@ impl Archetype {
             .map(|version| ArchetypeVersion::new(version.archetype_id, version.version))
             .collect())
     }
+
+    /// The contributors who may edit this archetype, in addition to the universe's maintainers.
+    /// If empty, any contributor may edit it.
+    fn editors(&self, context: &Context) -> FieldResult<Vec<Contributor>> {
+        let archetype = self.load(context)?;
+        Ok(context
+            .archetype_editors()
+            .for_archetype(&archetype.id)
+            .into_iter()
+            .map(|editor| Contributor::new(archetype.universe_id, editor.account_id))
+            .collect())
+    }
 }
 
 #[juniper::graphql_object(Context = Context, name = "ArchetypeResult")]
@@ -1,4 +1,4 @@
-use super::{Context, OperationResult, QueryWrapper};
+use super::{revision, AttachedAsset, Context, OperationResult, QueryWrapper, Universe};
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
 use juniper::FieldResult;
@@ -13,6 +13,8 @@ impl QueryWrapper for ArchetypeVersion {
     type Model = data::ArchetypeVersion;
 
     fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        let archetype = self.load_archetype(context)?;
+        Universe::new(archetype.universe_id).load(context)?;
         context
             .archetype_versions()
             .load((self.archetype_id, self.version))
@@ -64,6 +66,22 @@ impl ArchetypeVersion {
         Ok(self.load(context)?.script.to_owned())
     }
 
+    /// Identifies the current contents of the script. Pass this when updating the archetype to ensure
+    /// that no one else has changed it in the meantime.
+    fn revision(&self, context: &Context) -> FieldResult<String> {
+        Ok(revision::of(&self.load(context)?.script))
+    }
+
+    /// The artwork attached to this version of the archetype.
+    fn assets(&self, context: &Context) -> FieldResult<Vec<AttachedAsset>> {
+        Ok(context
+            .archetype_version_assets()
+            .for_archetype_version(&self.archetype_id, &self.version)
+            .into_iter()
+            .map(|asset| AttachedAsset::new(asset.name, asset.asset_hash))
+            .collect())
+    }
+
     /// When this version was created.
     fn created_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
         Ok(self.load(context)?.created_at)
@@ -0,0 +1,84 @@
+use super::{Account, Context, QueryWrapper};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use juniper::FieldResult;
+
+pub struct Asset {
+    hash: String,
+}
+
+impl QueryWrapper for Asset {
+    type Model = data::Asset;
+
+    fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        context
+            .assets()
+            .load(self.hash.clone())
+            .ok_or_else(|| anyhow!("Asset {} does not exist", self.hash))
+    }
+}
+
+impl Asset {
+    pub fn new(hash: String) -> Self {
+        Self { hash }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl Asset {
+    /// The SHA-256 hash of the asset's content, which identifies it.
+    fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// The MIME type of the asset's content.
+    fn content_type(&self, context: &Context) -> FieldResult<String> {
+        Ok(self.load(context)?.content_type)
+    }
+
+    /// The size of the asset's content, in bytes.
+    fn size(&self, context: &Context) -> FieldResult<i32> {
+        Ok(self.load(context)?.size)
+    }
+
+    /// The path, relative to this server, from which the asset's content can be downloaded.
+    fn url(&self) -> String {
+        format!("/assets/{}", self.hash)
+    }
+
+    /// The account that first uploaded this asset.
+    fn uploaded_by(&self, context: &Context) -> FieldResult<Account> {
+        Ok(Account::new(self.load(context)?.created_by))
+    }
+
+    /// When this asset was first uploaded.
+    fn created_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
+        Ok(self.load(context)?.created_at)
+    }
+}
+
+/// An asset attached to a version of an archetype or map, under a name describing its purpose
+/// (e.g. "sprite", "icon" or "thumbnail").
+pub struct AttachedAsset {
+    name: String,
+    hash: String,
+}
+
+impl AttachedAsset {
+    pub fn new(name: String, hash: String) -> Self {
+        Self { name, hash }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl AttachedAsset {
+    /// What the asset is used for.
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The asset itself.
+    fn asset(&self) -> Asset {
+        Asset::new(self.hash.clone())
+    }
+}
@@ -0,0 +1,365 @@
+use super::universe_diff::script_diff;
+use super::{revision, Account, Archetype, Context, Map, OperationResult, QueryWrapper, Universe};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use data::{ChangeRequestState, ContributorRole};
+use juniper::FieldResult;
+use uuid::Uuid;
+
+pub struct ChangeRequest {
+    id: Uuid,
+}
+
+impl QueryWrapper for ChangeRequest {
+    type Model = data::ChangeRequest;
+
+    fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        let change_request = context
+            .change_requests()
+            .load(self.id)
+            .ok_or_else(|| anyhow!("Change request {} does not exist", self.id))?;
+        Universe::new(change_request.universe_id).load(context)?;
+        Ok(change_request)
+    }
+}
+
+impl ChangeRequest {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl ChangeRequest {
+    /// The ID of the change request.
+    fn id(&self, context: &Context) -> FieldResult<Uuid> {
+        Ok(self.load(context)?.id)
+    }
+
+    /// The universe this change request would modify.
+    fn universe(&self, context: &Context) -> FieldResult<Universe> {
+        Ok(Universe::new(self.load(context)?.universe_id))
+    }
+
+    /// The contributor who opened this change request.
+    fn author(&self, context: &Context) -> FieldResult<Account> {
+        Ok(Account::new(self.load(context)?.author_id))
+    }
+
+    /// A short summary of the change.
+    fn title(&self, context: &Context) -> FieldResult<String> {
+        Ok(self.load(context)?.title)
+    }
+
+    /// A longer explanation of the change.
+    fn description(&self, context: &Context) -> FieldResult<Option<String>> {
+        Ok(self.load(context)?.description)
+    }
+
+    /// Whether this change request is still open, or has been merged or closed.
+    fn state(&self, context: &Context) -> FieldResult<ChangeRequestState> {
+        Ok(self.load(context)?.state)
+    }
+
+    /// The maintainer who merged this change request, if it has been merged.
+    fn merged_by(&self, context: &Context) -> FieldResult<Option<Account>> {
+        Ok(self.load(context)?.merged_by.map(Account::new))
+    }
+
+    /// The archetype scripts this change request proposes.
+    fn archetypes(&self, context: &Context) -> FieldResult<Vec<ProposedArchetype>> {
+        Ok(context
+            .change_request_archetypes()
+            .for_change_request(&self.load(context)?.id)
+            .into_iter()
+            .map(|proposed| {
+                ProposedArchetype::new(proposed.change_request_id, proposed.archetype_id)
+            })
+            .collect())
+    }
+
+    /// The map scripts this change request proposes.
+    fn maps(&self, context: &Context) -> FieldResult<Vec<ProposedMap>> {
+        Ok(context
+            .change_request_maps()
+            .for_change_request(&self.load(context)?.id)
+            .into_iter()
+            .map(|proposed| ProposedMap::new(proposed.change_request_id, proposed.map_id))
+            .collect())
+    }
+
+    /// The reviewers who have approved the current contents of this change request. Approvals
+    /// from accounts who are no longer able to review are not included.
+    fn approvals(&self, context: &Context) -> FieldResult<Vec<Account>> {
+        let change_request = self.load(context)?;
+        Ok(context
+            .change_request_approvals()
+            .for_change_request(&change_request.id)
+            .into_iter()
+            .filter(|approval| {
+                context
+                    .contributors()
+                    .load((change_request.universe_id, approval.account_id))
+                    .map(|contributor| {
+                        matches!(
+                            contributor.role,
+                            ContributorRole::Owner
+                                | ContributorRole::Maintainer
+                                | ContributorRole::Contributor
+                                | ContributorRole::Reviewer
+                        )
+                    })
+                    .unwrap_or(false)
+            })
+            .map(|approval| Account::new(approval.account_id))
+            .collect())
+    }
+
+    /// Discussion of this change request, oldest first.
+    fn comments(&self, context: &Context) -> FieldResult<Vec<ChangeRequestComment>> {
+        let mut comments = context
+            .change_request_comments()
+            .for_change_request(&self.load(context)?.id);
+        comments.sort_by_key(|comment| comment.created_at);
+        Ok(comments
+            .into_iter()
+            .map(|comment| ChangeRequestComment::new(comment.id))
+            .collect())
+    }
+
+    /// When this change request was opened.
+    fn created_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
+        Ok(self.load(context)?.created_at)
+    }
+
+    /// When this change request was last updated.
+    fn updated_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
+        Ok(self.load(context)?.updated_at)
+    }
+
+    /// When this change request was merged or closed.
+    fn closed_at(&self, context: &Context) -> FieldResult<Option<DateTime<Utc>>> {
+        Ok(self.load(context)?.closed_at)
+    }
+}
+
+#[juniper::graphql_object(Context = Context, name = "ChangeRequestResult")]
+impl OperationResult<ChangeRequest> {
+    pub fn success(&self) -> Option<&ChangeRequest> {
+        self.success()
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error()
+    }
+}
+
+pub struct ProposedArchetype {
+    change_request_id: Uuid,
+    archetype_id: Uuid,
+}
+
+impl QueryWrapper for ProposedArchetype {
+    type Model = data::ChangeRequestArchetype;
+
+    fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        context
+            .change_request_archetypes()
+            .load((self.change_request_id, self.archetype_id))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Change request {} does not modify archetype {}",
+                    self.change_request_id,
+                    self.archetype_id
+                )
+            })
+    }
+}
+
+impl ProposedArchetype {
+    pub fn new(change_request_id: Uuid, archetype_id: Uuid) -> Self {
+        Self {
+            change_request_id,
+            archetype_id,
+        }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl ProposedArchetype {
+    /// The archetype which would be modified.
+    fn archetype(&self) -> Archetype {
+        Archetype::new(self.archetype_id)
+    }
+
+    /// The proposed script.
+    fn script(&self, context: &Context) -> FieldResult<String> {
+        Ok(self.load(context)?.script)
+    }
+
+    /// The revision of the archetype's script that the proposed script is based on.
+    fn base_revision(&self, context: &Context) -> FieldResult<String> {
+        Ok(self.load(context)?.base_revision)
+    }
+
+    /// Whether the archetype has been changed since the revision the proposed script is based on.
+    /// The change request cannot be merged until the proposed script is updated.
+    fn conflicted(&self, context: &Context) -> FieldResult<bool> {
+        let current_revision = context
+            .archetype_versions()
+            .for_archetype(&self.archetype_id)
+            .into_iter()
+            .max_by_key(|version| version.version)
+            .map(|version| revision::of(&version.script));
+        Ok(current_revision != Some(self.load(context)?.base_revision))
+    }
+
+    /// A unified diff from the archetype's latest version to the proposed script.
+    fn script_diff(&self, context: &Context) -> FieldResult<String> {
+        let name = Archetype::new(self.archetype_id).load(context)?.name;
+        let latest = context
+            .archetype_versions()
+            .for_archetype(&self.archetype_id)
+            .into_iter()
+            .max_by_key(|version| version.version)
+            .map(|version| (version.version, version.script));
+        let proposed = self.load(context)?.script;
+        let next = latest.as_ref().map(|(version, _)| version + 1).unwrap_or(1);
+        Ok(script_diff(&name, latest, Some((next, proposed))))
+    }
+}
+
+pub struct ProposedMap {
+    change_request_id: Uuid,
+    map_id: Uuid,
+}
+
+impl QueryWrapper for ProposedMap {
+    type Model = data::ChangeRequestMap;
+
+    fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        context
+            .change_request_maps()
+            .load((self.change_request_id, self.map_id))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Change request {} does not modify map {}",
+                    self.change_request_id,
+                    self.map_id
+                )
+            })
+    }
+}
+
+impl ProposedMap {
+    pub fn new(change_request_id: Uuid, map_id: Uuid) -> Self {
+        Self {
+            change_request_id,
+            map_id,
+        }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl ProposedMap {
+    /// The map which would be modified.
+    fn map(&self) -> Map {
+        Map::new(self.map_id)
+    }
+
+    /// The proposed script.
+    fn script(&self, context: &Context) -> FieldResult<String> {
+        Ok(self.load(context)?.script)
+    }
+
+    /// The revision of the map's script that the proposed script is based on.
+    fn base_revision(&self, context: &Context) -> FieldResult<String> {
+        Ok(self.load(context)?.base_revision)
+    }
+
+    /// Whether the map has been changed since the revision the proposed script is based on.
+    /// The change request cannot be merged until the proposed script is updated.
+    fn conflicted(&self, context: &Context) -> FieldResult<bool> {
+        let current_revision = context
+            .map_versions()
+            .for_map(&self.map_id)
+            .into_iter()
+            .max_by_key(|version| version.version)
+            .map(|version| revision::of(&version.script));
+        Ok(current_revision != Some(self.load(context)?.base_revision))
+    }
+
+    /// A unified diff from the map's latest version to the proposed script.
+    fn script_diff(&self, context: &Context) -> FieldResult<String> {
+        let name = Map::new(self.map_id).load(context)?.name;
+        let latest = context
+            .map_versions()
+            .for_map(&self.map_id)
+            .into_iter()
+            .max_by_key(|version| version.version)
+            .map(|version| (version.version, version.script));
+        let proposed = self.load(context)?.script;
+        let next = latest.as_ref().map(|(version, _)| version + 1).unwrap_or(1);
+        Ok(script_diff(&name, latest, Some((next, proposed))))
+    }
+}
+
+pub struct ChangeRequestComment {
+    id: Uuid,
+}
+
+impl QueryWrapper for ChangeRequestComment {
+    type Model = data::ChangeRequestComment;
+
+    fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        context
+            .change_request_comments()
+            .load(self.id)
+            .ok_or_else(|| anyhow!("Change request comment {} does not exist", self.id))
+    }
+}
+
+impl ChangeRequestComment {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl ChangeRequestComment {
+    /// The ID of the comment.
+    fn id(&self, context: &Context) -> FieldResult<Uuid> {
+        Ok(self.load(context)?.id)
+    }
+
+    /// The change request being discussed.
+    fn change_request(&self, context: &Context) -> FieldResult<ChangeRequest> {
+        Ok(ChangeRequest::new(self.load(context)?.change_request_id))
+    }
+
+    /// The account that wrote the comment.
+    fn author(&self, context: &Context) -> FieldResult<Account> {
+        Ok(Account::new(self.load(context)?.account_id))
+    }
+
+    /// The text of the comment.
+    fn body(&self, context: &Context) -> FieldResult<String> {
+        Ok(self.load(context)?.body)
+    }
+
+    /// When the comment was written.
+    fn created_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
+        Ok(self.load(context)?.created_at)
+    }
+}
+
+#[juniper::graphql_object(Context = Context, name = "ChangeRequestCommentResult")]
+impl OperationResult<ChangeRequestComment> {
+    pub fn success(&self) -> Option<&ChangeRequestComment> {
+        self.success()
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error()
+    }
+}
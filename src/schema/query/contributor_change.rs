@@ -0,0 +1,56 @@
+use super::{Account, Context, QueryWrapper, Universe};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use data::ContributorRole;
+use juniper::FieldResult;
+use uuid::Uuid;
+
+pub struct ContributorChange {
+    id: Uuid,
+}
+
+impl QueryWrapper for ContributorChange {
+    type Model = data::ContributorChange;
+
+    fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        context
+            .contributor_changes()
+            .load(self.id)
+            .ok_or_else(|| anyhow!("Contributor change {} does not exist", self.id))
+    }
+}
+
+impl ContributorChange {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl ContributorChange {
+    /// The account whose role was changed.
+    fn account(&self, context: &Context) -> FieldResult<Account> {
+        Ok(Account::new(self.load(context)?.account_id))
+    }
+
+    /// The universe in which the role was changed.
+    fn universe(&self, context: &Context) -> FieldResult<Universe> {
+        Ok(Universe::new(self.load(context)?.universe_id))
+    }
+
+    /// The account that made the change. If null, the change was made automatically, such as
+    /// when an invitation expired.
+    fn performed_by(&self, context: &Context) -> FieldResult<Option<Account>> {
+        Ok(self.load(context)?.performed_by.map(Account::new))
+    }
+
+    /// The role the account was given. If null, the account was removed from the universe.
+    fn role(&self, context: &Context) -> FieldResult<Option<ContributorRole>> {
+        Ok(self.load(context)?.role)
+    }
+
+    /// When the change was made.
+    fn created_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
+        Ok(self.load(context)?.created_at)
+    }
+}
@@ -1,11 +1,24 @@
 use super::{
-    Context, Entity, MapVersion, OperationResult, Pagination, Player, QueryWrapper, UniverseVersion,
+    time_control, Context, Entity, GameMigration, GameOutcome, Invite, MapVersion, OperationResult,
+    Pagination, Player, QueryWrapper, UniverseVersion,
 };
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
+use data::{DepartureRule, PlayerEngagement, TimeoutAction};
 use juniper::FieldResult;
 use uuid::Uuid;
 
+/// Where a game is in its lifecycle.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, juniper::GraphQLEnum)]
+pub enum GameStatus {
+    /// The game is waiting for players to fill its seats.
+    Lobby,
+    /// The game is being played.
+    InProgress,
+    /// The game has ended, and its result is final.
+    Ended,
+}
+
 pub struct Game {
     id: Uuid,
 }
@@ -25,6 +38,17 @@ impl Game {
     pub fn new(id: Uuid) -> Self {
         Self { id }
     }
+
+    fn load_current_player(&self, context: &Context) -> anyhow::Result<Option<data::Player>> {
+        let game = self.load(context)?;
+        Ok(game.current_turn.and_then(|current_turn| {
+            context
+                .players()
+                .for_game(&game.id)
+                .into_iter()
+                .find(|player| player.turn_order == current_turn)
+        }))
+    }
 }
 
 #[juniper::graphql_object(Context = Context)]
@@ -82,11 +106,108 @@ impl Game {
         Ok(self.load(context)?.state.to_string())
     }
 
-    /// When this game was started.
+    /// Whether the game is waiting for players, being played, or has ended.
+    fn status(&self, context: &Context) -> FieldResult<GameStatus> {
+        let game = self.load(context)?;
+        Ok(if game.ended_at.is_some() {
+            GameStatus::Ended
+        } else if game.started_at.is_some() {
+            GameStatus::InProgress
+        } else {
+            GameStatus::Lobby
+        })
+    }
+
+    /// The outcome of the game. Null until the game has ended.
+    fn result(&self, context: &Context) -> FieldResult<Option<GameOutcome>> {
+        let game = self.load(context)?;
+        Ok(game.ended_at.map(|_| GameOutcome::new(game.id)))
+    }
+
+    /// When this game was created.
     fn created_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
         Ok(self.load(context)?.created_at)
     }
 
+    /// When every seat in this game was filled, and the game began. Null while the game is still
+    /// waiting for players.
+    fn started_at(&self, context: &Context) -> FieldResult<Option<DateTime<Utc>>> {
+        Ok(self.load(context)?.started_at)
+    }
+
+    /// The number of players this game is for.
+    fn seats(&self, context: &Context) -> FieldResult<i32> {
+        Ok(self.load(context)?.seats)
+    }
+
+    /// The number of seats that anyone may still join.
+    fn open_seats(&self, context: &Context) -> FieldResult<i32> {
+        let game = self.load(context)?;
+        let taken = context
+            .players()
+            .for_game(&game.id)
+            .into_iter()
+            .filter(|player| PlayerEngagement::TAKING_SEAT.contains(&player.engagement))
+            .count();
+        Ok(game.seats - taken as i32)
+    }
+
+    /// The number of seconds each player has to take a turn, if turns are limited.
+    fn turn_limit(&self, context: &Context) -> FieldResult<Option<i32>> {
+        Ok(self.load(context)?.turn_limit)
+    }
+
+    /// The number of seconds each player started with in their time bank, which is drawn on once
+    /// the turn limit runs out, like a chess clock.
+    fn time_bank(&self, context: &Context) -> FieldResult<Option<i32>> {
+        Ok(self.load(context)?.time_bank)
+    }
+
+    /// What happens to a player who runs out of time on their turn.
+    fn on_timeout(&self, context: &Context) -> FieldResult<TimeoutAction> {
+        Ok(self.load(context)?.timeout_action)
+    }
+
+    /// The number of timeouts after which a player forfeits, if players forfeit on timeout.
+    fn forfeit_after(&self, context: &Context) -> FieldResult<Option<i32>> {
+        let game = self.load(context)?;
+        Ok(match game.timeout_action {
+            TimeoutAction::Forfeit => Some(game.forfeit_after.unwrap_or(1)),
+            TimeoutAction::SkipTurn => None,
+        })
+    }
+
+    /// What becomes of a player's entities when they leave the game.
+    fn on_departure(&self, context: &Context) -> FieldResult<DepartureRule> {
+        Ok(self.load(context)?.departure_rule)
+    }
+
+    /// When the game ended. Null until the game has ended.
+    fn ended_at(&self, context: &Context) -> FieldResult<Option<DateTime<Utc>>> {
+        Ok(self.load(context)?.ended_at)
+    }
+
+    /// The player whose turn it currently is. Null unless the game is in progress.
+    fn current_player(&self, context: &Context) -> FieldResult<Option<Player>> {
+        let game = self.load(context)?;
+        Ok(self
+            .load_current_player(context)?
+            .map(|player| Player::new(game.id, player.account_id)))
+    }
+
+    /// When the current turn began.
+    fn turn_started_at(&self, context: &Context) -> FieldResult<Option<DateTime<Utc>>> {
+        Ok(self.load(context)?.turn_started_at)
+    }
+
+    /// When the current turn will time out, if the game has a time control.
+    fn turn_deadline(&self, context: &Context) -> FieldResult<Option<DateTime<Utc>>> {
+        let game = self.load(context)?;
+        Ok(self
+            .load_current_player(context)?
+            .and_then(|player| time_control::turn_deadline(&game, &player)))
+    }
+
     /// The players in this game.
     fn players(&self, context: &Context) -> FieldResult<Vec<Player>> {
         Ok(context
@@ -97,6 +218,21 @@ impl Game {
             .collect())
     }
 
+    /// The invites you have issued for this game.
+    fn invites(&self, context: &Context) -> FieldResult<Vec<Invite>> {
+        let mut invites: Vec<_> = context
+            .invites()
+            .for_game(&self.load(context)?.id)
+            .into_iter()
+            .filter(|invite| Some(invite.created_by) == context.authenticated_account())
+            .collect();
+        invites.sort_by_key(|invite| std::cmp::Reverse(invite.created_at));
+        Ok(invites
+            .into_iter()
+            .map(|invite| Invite::new(invite.id))
+            .collect())
+    }
+
     /// The entities that currently exist in game.
     fn entities(&self, context: &Context) -> FieldResult<Vec<Entity>> {
         Ok(context
@@ -106,6 +242,14 @@ impl Game {
             .map(|entity| Entity::new(entity.id))
             .collect())
     }
+
+    /// The proposed migration of this game to a newer version of its universe, if any.
+    fn migration(&self, context: &Context) -> FieldResult<Option<GameMigration>> {
+        Ok(context
+            .game_migrations()
+            .load(self.load(context)?.id)
+            .map(|migration| GameMigration::new(migration.game_id)))
+    }
 }
 
 #[juniper::graphql_object(Context = Context, name = "GamePagination")]
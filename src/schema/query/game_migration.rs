@@ -0,0 +1,109 @@
+use super::{Context, Game, Player, QueryWrapper, UniverseVersion};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use juniper::FieldResult;
+use uuid::Uuid;
+
+pub struct GameMigration {
+    game_id: Uuid,
+}
+
+impl QueryWrapper for GameMigration {
+    type Model = data::GameMigration;
+
+    fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        context
+            .game_migrations()
+            .load(self.game_id)
+            .ok_or_else(|| anyhow!("Game {} has no proposed migration", self.game_id))
+    }
+}
+
+impl GameMigration {
+    pub fn new(game_id: Uuid) -> Self {
+        Self { game_id }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl GameMigration {
+    /// The game which is to be migrated.
+    fn game(&self, context: &Context) -> FieldResult<Game> {
+        Ok(Game::new(self.load(context)?.game_id))
+    }
+
+    /// The version of the universe the game would be migrated to.
+    fn universe(&self, context: &Context) -> FieldResult<UniverseVersion> {
+        let migration = self.load(context)?;
+        let game = Game::new(migration.game_id).load(context)?;
+        Ok(UniverseVersion::new(
+            game.universe_id,
+            migration.universe_version,
+        ))
+    }
+
+    /// When the migration was proposed.
+    fn proposed_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
+        Ok(self.load(context)?.created_at)
+    }
+
+    /// The votes that have been cast so far.
+    fn votes(&self, context: &Context) -> FieldResult<Vec<GameMigrationVote>> {
+        Ok(context
+            .game_migration_votes()
+            .for_game(&self.load(context)?.game_id)
+            .into_iter()
+            .map(|vote| GameMigrationVote::new(vote.game_id, vote.account_id))
+            .collect())
+    }
+}
+
+pub struct GameMigrationVote {
+    game_id: Uuid,
+    account_id: Uuid,
+}
+
+impl QueryWrapper for GameMigrationVote {
+    type Model = data::GameMigrationVote;
+
+    fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        context
+            .game_migration_votes()
+            .load((self.game_id, self.account_id))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Game {} player {} has not voted on the migration",
+                    self.game_id,
+                    self.account_id
+                )
+            })
+    }
+}
+
+impl GameMigrationVote {
+    pub fn new(game_id: Uuid, account_id: Uuid) -> Self {
+        Self {
+            game_id,
+            account_id,
+        }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl GameMigrationVote {
+    /// The player who voted.
+    fn player(&self, context: &Context) -> FieldResult<Player> {
+        let vote = self.load(context)?;
+        Ok(Player::new(vote.game_id, vote.account_id))
+    }
+
+    /// Whether the player is in favour of the migration.
+    fn accepted(&self, context: &Context) -> FieldResult<bool> {
+        Ok(self.load(context)?.accepted)
+    }
+
+    /// When the vote was cast.
+    fn voted_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
+        Ok(self.load(context)?.created_at)
+    }
+}
@@ -0,0 +1,107 @@
+use super::{Context, Game, Player, QueryWrapper};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use juniper::FieldResult;
+use uuid::Uuid;
+
+/// The outcome of a game that has ended.
+pub struct GameOutcome {
+    game_id: Uuid,
+}
+
+impl GameOutcome {
+    pub fn new(game_id: Uuid) -> Self {
+        Self { game_id }
+    }
+
+    fn load_placements(&self, context: &Context) -> Vec<data::GameResult> {
+        let mut placements = context.game_results().for_game(&self.game_id);
+        placements.sort_by_key(|placement| placement.placement);
+        placements
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl GameOutcome {
+    /// The game that ended.
+    fn game(&self) -> Game {
+        Game::new(self.game_id)
+    }
+
+    /// When the game ended.
+    fn ended_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
+        Ok(Game::new(self.game_id)
+            .load(context)?
+            .ended_at
+            .ok_or_else(|| anyhow!("Game {} has not ended", self.game_id))?)
+    }
+
+    /// The players who placed first. If more than one player placed first, the game was a draw.
+    fn winners(&self, context: &Context) -> Vec<Player> {
+        let placements = self.load_placements(context);
+        let first = placements.first().map(|placement| placement.placement);
+        placements
+            .into_iter()
+            .filter(|placement| Some(placement.placement) == first)
+            .map(|placement| Player::new(placement.game_id, placement.account_id))
+            .collect()
+    }
+
+    /// Where each player placed, best first. Players removed by the host are not placed.
+    fn placements(&self, context: &Context) -> Vec<PlayerResult> {
+        self.load_placements(context)
+            .into_iter()
+            .map(|placement| PlayerResult::new(placement.game_id, placement.account_id))
+            .collect()
+    }
+}
+
+pub struct PlayerResult {
+    game_id: Uuid,
+    account_id: Uuid,
+}
+
+impl QueryWrapper for PlayerResult {
+    type Model = data::GameResult;
+
+    fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        context
+            .game_results()
+            .load((self.game_id, self.account_id))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Game {} player {} has no result",
+                    self.game_id,
+                    self.account_id
+                )
+            })
+    }
+}
+
+impl PlayerResult {
+    pub fn new(game_id: Uuid, account_id: Uuid) -> Self {
+        Self {
+            game_id,
+            account_id,
+        }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl PlayerResult {
+    /// The player this result is for.
+    fn player(&self, context: &Context) -> FieldResult<Player> {
+        let result = self.load(context)?;
+        Ok(Player::new(result.game_id, result.account_id))
+    }
+
+    /// Where the player placed, starting from 1. Players who share a placement drew.
+    fn placement(&self, context: &Context) -> FieldResult<i32> {
+        Ok(self.load(context)?.placement)
+    }
+
+    /// The player's final score, if the map keeps score.
+    fn score(&self, context: &Context) -> FieldResult<Option<f64>> {
+        Ok(self.load(context)?.score)
+    }
+}
@@ -0,0 +1,102 @@
+use super::{Account, Context, Game, OperationResult, QueryWrapper, Universe};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use data::ContributorRole;
+use juniper::FieldResult;
+use uuid::Uuid;
+
+pub struct Invite {
+    id: Uuid,
+}
+
+impl QueryWrapper for Invite {
+    type Model = data::Invite;
+
+    fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        context
+            .invites()
+            .load(self.id)
+            .ok_or_else(|| anyhow!("Invite {} does not exist", self.id))
+    }
+}
+
+impl Invite {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl Invite {
+    /// The ID of the invite.
+    fn id(&self, context: &Context) -> FieldResult<Uuid> {
+        Ok(self.load(context)?.id)
+    }
+
+    /// The signed token which can be shared for others to redeem the invite. Only the account
+    /// which issued the invite may see it.
+    fn token(&self, context: &Context) -> FieldResult<String> {
+        let invite = self.load(context)?;
+        if context.authenticated_account() != Some(invite.created_by) {
+            return Err(anyhow!("Only the issuer of an invite may see its token").into());
+        }
+        Ok(crate::jwt::encode_invite(&invite)?)
+    }
+
+    /// The game in which the invite offers a seat, if it is an invite to a game.
+    fn game(&self, context: &Context) -> FieldResult<Option<Game>> {
+        Ok(self.load(context)?.game_id.map(Game::new))
+    }
+
+    /// The universe the invite offers a role in, if it is an invite to contribute to a universe.
+    fn universe(&self, context: &Context) -> FieldResult<Option<Universe>> {
+        Ok(self.load(context)?.universe_id.map(Universe::new))
+    }
+
+    /// The role given to those who redeem an invite to contribute to a universe.
+    fn role(&self, context: &Context) -> FieldResult<Option<ContributorRole>> {
+        Ok(self.load(context)?.role)
+    }
+
+    /// The account which issued the invite.
+    fn created_by(&self, context: &Context) -> FieldResult<Account> {
+        Ok(Account::new(self.load(context)?.created_by))
+    }
+
+    /// When the invite was issued.
+    fn created_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
+        Ok(self.load(context)?.created_at)
+    }
+
+    /// When the invite can no longer be redeemed.
+    fn expires_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
+        Ok(self.load(context)?.expires_at)
+    }
+
+    /// The number of times the invite may be redeemed. If null, it may be redeemed any number of
+    /// times until it expires.
+    fn max_uses(&self, context: &Context) -> FieldResult<Option<i32>> {
+        Ok(self.load(context)?.max_uses)
+    }
+
+    /// The number of times the invite has been redeemed.
+    fn uses(&self, context: &Context) -> FieldResult<i32> {
+        Ok(self.load(context)?.uses)
+    }
+
+    /// When the issuer revoked the invite, if they have.
+    fn revoked_at(&self, context: &Context) -> FieldResult<Option<DateTime<Utc>>> {
+        Ok(self.load(context)?.revoked_at)
+    }
+}
+
+#[juniper::graphql_object(Context = Context, name = "InviteResult")]
+impl OperationResult<Invite> {
+    pub fn success(&self) -> Option<&Invite> {
+        self.success()
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error()
+    }
+}
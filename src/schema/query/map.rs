@@ -1,4 +1,4 @@
-use super::{Context, MapVersion, QueryWrapper};
+use super::{Context, Contributor, MapVersion, OperationResult, QueryWrapper, Universe};
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
 use juniper::FieldResult;
@@ -12,10 +12,12 @@ impl QueryWrapper for Map {
     type Model = data::Map;
 
     fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
-        context
+        let map = context
             .maps()
             .load(self.id)
-            .ok_or_else(|| anyhow!("Map {} does not exist", self.id))
+            .ok_or_else(|| anyhow!("Map {} does not exist", self.id))?;
+        Universe::new(map.universe_id).load(context)?;
+        Ok(map)
     }
 }
 
@@ -37,6 +39,27 @@ impl Map {
         Ok(self.load(context)?.name.to_owned())
     }
 
+    /// The name of the map to show to players.
+    fn display_name(&self, context: &Context) -> FieldResult<Option<String>> {
+        Ok(self.load(context)?.display_name)
+    }
+
+    /// A description of the map.
+    fn description(&self, context: &Context) -> FieldResult<Option<String>> {
+        Ok(self.load(context)?.description)
+    }
+
+    /// Tags used to categorize the map.
+    fn tags(&self, context: &Context) -> FieldResult<Vec<String>> {
+        Ok(self.load(context)?.tags)
+    }
+
+    /// When this map was deleted. Deleted maps are kept while released versions of their
+    /// universe still use them.
+    fn deleted_at(&self, context: &Context) -> FieldResult<Option<DateTime<Utc>>> {
+        Ok(self.load(context)?.deleted_at)
+    }
+
     /// When this map was created.
     fn created_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
         Ok(self.load(context)?.created_at)
@@ -51,4 +74,27 @@ impl Map {
             .map(|version| MapVersion::new(version.map_id, version.version))
             .collect())
     }
+
+    /// The contributors who may edit this map, in addition to the universe's maintainers. If
+    /// empty, any contributor may edit it.
+    fn editors(&self, context: &Context) -> FieldResult<Vec<Contributor>> {
+        let map = self.load(context)?;
+        Ok(context
+            .map_editors()
+            .for_map(&map.id)
+            .into_iter()
+            .map(|editor| Contributor::new(map.universe_id, editor.account_id))
+            .collect())
+    }
+}
+
+#[juniper::graphql_object(Context = Context, name = "MapResult")]
+impl OperationResult<Map> {
+    pub fn success(&self) -> Option<&Map> {
+        self.success()
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error()
+    }
 }
@@ -1,4 +1,4 @@
-use super::{Context, OperationResult, QueryWrapper};
+use super::{revision, AttachedAsset, Context, OperationResult, QueryWrapper, Universe};
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
 use juniper::FieldResult;
@@ -13,6 +13,8 @@ impl QueryWrapper for MapVersion {
     type Model = data::MapVersion;
 
     fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        let map = self.load_map(context)?;
+        Universe::new(map.universe_id).load(context)?;
         context
             .map_versions()
             .load((self.map_id, self.version))
@@ -61,6 +63,22 @@ impl MapVersion {
         Ok(self.load(context)?.script.to_owned())
     }
 
+    /// Identifies the current contents of the script. Pass this when updating the map to ensure
+    /// that no one else has changed it in the meantime.
+    fn revision(&self, context: &Context) -> FieldResult<String> {
+        Ok(revision::of(&self.load(context)?.script))
+    }
+
+    /// The artwork attached to this version of the map.
+    fn assets(&self, context: &Context) -> FieldResult<Vec<AttachedAsset>> {
+        Ok(context
+            .map_version_assets()
+            .for_map_version(&self.map_id, &self.version)
+            .into_iter()
+            .map(|asset| AttachedAsset::new(asset.name, asset.asset_hash))
+            .collect())
+    }
+
     /// When this version was created.
     fn created_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
         Ok(self.load(context)?.created_at)
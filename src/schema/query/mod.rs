@@ -1,4 +1,4 @@
-use super::Context;
+use super::{revision, time_control, validation, Context};
 use juniper::FieldResult;
 use uuid::Uuid;
 
@@ -14,27 +14,51 @@ pub use operation_result::OperationResult;
 mod account;
 mod archetype;
 mod archetype_version;
+mod asset;
+mod change_request;
 mod contributor;
+mod contributor_change;
 mod email;
 mod entity;
 mod game;
+mod game_migration;
+mod game_outcome;
+mod invite;
 mod map;
 mod map_version;
+mod notification;
 mod player;
+mod player_rating;
+mod search;
 mod universe;
+mod universe_diff;
+mod universe_stats;
+mod universe_validation;
 mod universe_version;
 
 pub use account::Account;
 pub use archetype::Archetype;
 pub use archetype_version::ArchetypeVersion;
+pub use asset::{Asset, AttachedAsset};
+pub use change_request::{ChangeRequest, ChangeRequestComment};
 pub use contributor::Contributor;
+pub use contributor_change::ContributorChange;
 pub use email::Email;
 pub use entity::Entity;
 pub use game::Game;
+pub use game_migration::{GameMigration, GameMigrationVote};
+pub use game_outcome::GameOutcome;
+pub use invite::Invite;
 pub use map::Map;
 pub use map_version::MapVersion;
+pub use notification::Notification;
 pub use player::Player;
+pub use player_rating::PlayerRating;
+pub use search::SearchResult;
 pub use universe::Universe;
+pub use universe_diff::UniverseDiff;
+pub use universe_stats::UniverseStats;
+pub use universe_validation::UniverseValidation;
 pub use universe_version::UniverseVersion;
 
 pub struct Query;
@@ -56,7 +80,33 @@ impl Query {
         Game::new(id)
     }
 
-    /// Look up a version of a universe. If version is not specified, looks up the current (released) version.
+    /// Look up an uploaded asset by its hash.
+    fn asset(hash: String) -> Asset {
+        Asset::new(hash)
+    }
+
+    /// Look up a change request.
+    fn change_request(id: Uuid) -> ChangeRequest {
+        ChangeRequest::new(id)
+    }
+
+    /// Games in public universes which have not yet started and still have open seats, most
+    /// recently created first.
+    fn open_games(
+        context: &Context,
+        search: Option<data::OpenGameSearch>,
+    ) -> FieldResult<Pagination<Game>> {
+        let search = data::GameSearch::from_open_game_search(search.unwrap_or_default());
+        let items = context
+            .games()
+            .search(&search)?
+            .into_iter()
+            .map(|game| Game::new(game.id));
+        Ok(Pagination::new(search, items))
+    }
+
+    /// Look up a version of a universe. If version is not specified, looks up the current (released,
+    /// not yanked) version.
     fn universe(context: &Context, id: Uuid, version: Option<i32>) -> FieldResult<UniverseVersion> {
         let version = match version {
             Some(version) => version,
@@ -69,12 +119,55 @@ impl Query {
         Ok(UniverseVersion::new(id, version))
     }
 
-    /// Search for universes.
+    /// Check whether a version of a universe is ready to be published. If version is not
+    /// specified, checks the most recent (likely unreleased) version. Only contributors to the
+    /// universe may check it.
+    fn validate_universe(
+        context: &Context,
+        id: Uuid,
+        version: Option<i32>,
+    ) -> FieldResult<UniverseValidation> {
+        let universe = Universe::new(id);
+        universe.load(context)?;
+        universe.assert_contributor(context)?;
+        let version = match version {
+            Some(version) => version,
+            None => context
+                .universe_versions()
+                .load_current(id, true)?
+                .map(|version| version.version)
+                .ok_or_else(|| anyhow::anyhow!("Universe {} does not exist", id))?,
+        };
+        let scripts = context
+            .transaction(|conn| validation::UniverseVersionScripts::load(id, version, conn))?;
+        let validation = tokio::task::block_in_place(|| scripts.validate());
+        Ok(UniverseValidation::new(id, version, validation.problems))
+    }
+
+    /// Search the names, descriptions and tags of universes, archetypes and maps, best matches
+    /// first. Supports web search syntax (quoted phrases, `or` and `-` to exclude words). By
+    /// default, every kind of result is included. Only universes which would appear in a universe
+    /// search are searched, and only the archetypes and maps belonging to those universes.
+    fn search(
+        context: &Context,
+        search: data::TextSearch,
+    ) -> FieldResult<Pagination<SearchResult>> {
+        let search = search.visible_to(context.authenticated_account());
+        let items = SearchResult::search(context, &search)?;
+        Ok(Pagination::new(search, items))
+    }
+
+    /// Search for universes. Only public universes, and universes you are involved in, are
+    /// included. Universes are sorted by name, unless another order is chosen.
     fn universes(
         context: &Context,
         search: Option<data::UniverseSearch>,
+        order: Option<data::UniverseOrder>,
     ) -> FieldResult<Pagination<Universe>> {
-        let search = search.unwrap_or_default();
+        let search = search
+            .unwrap_or_default()
+            .visible_to(context.authenticated_account())
+            .ordered_by(order.unwrap_or(data::UniverseOrder::Name));
         let items = context
             .universes()
             .search(&search)?
@@ -0,0 +1,79 @@
+use super::{Context, Game, Pagination, QueryWrapper, Universe};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use data::NotificationKind;
+use juniper::FieldResult;
+use uuid::Uuid;
+
+pub struct Notification {
+    id: Uuid,
+}
+
+impl QueryWrapper for Notification {
+    type Model = data::Notification;
+
+    fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        context
+            .notifications()
+            .load(self.id)
+            .ok_or_else(|| anyhow!("Notification {} does not exist", self.id))
+    }
+}
+
+impl Notification {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl Notification {
+    /// The ID of the notification.
+    fn id(&self, context: &Context) -> FieldResult<Uuid> {
+        Ok(self.load(context)?.id)
+    }
+
+    /// What the notification is about.
+    fn kind(&self, context: &Context) -> FieldResult<NotificationKind> {
+        Ok(self.load(context)?.kind)
+    }
+
+    /// The game the notification is about, if any.
+    fn game(&self, context: &Context) -> FieldResult<Option<Game>> {
+        Ok(self.load(context)?.game_id.map(Game::new))
+    }
+
+    /// The universe the notification is about, if any.
+    fn universe(&self, context: &Context) -> FieldResult<Option<Universe>> {
+        Ok(self.load(context)?.universe_id.map(Universe::new))
+    }
+
+    /// When the notification was sent.
+    fn created_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
+        Ok(self.load(context)?.created_at)
+    }
+
+    /// When the notification was marked as read. Null while it is unread.
+    fn read_at(&self, context: &Context) -> FieldResult<Option<DateTime<Utc>>> {
+        Ok(self.load(context)?.read_at)
+    }
+}
+
+#[juniper::graphql_object(Context = Context, name = "NotificationPagination")]
+impl Pagination<Notification> {
+    fn items(&self) -> &[Notification] {
+        self.items()
+    }
+
+    fn total(&self) -> i32 {
+        self.total()
+    }
+
+    fn start(&self, context: &Context) -> juniper::FieldResult<Option<String>> {
+        self.start(context)
+    }
+
+    fn end(&self, context: &Context) -> juniper::FieldResult<Option<String>> {
+        self.end(context)
+    }
+}
@@ -1,5 +1,6 @@
 use super::{Context, QueryWrapper};
 use anyhow::anyhow;
+use chrono::{DateTime, Utc};
 use data::PlayerEngagement;
 use juniper::FieldResult;
 use uuid::Uuid;
@@ -64,6 +65,21 @@ impl Player {
         Ok(self.load(context)?.engagement)
     }
 
+    /// The number of seconds left in this player's time bank, if the game has one.
+    fn time_bank(&self, context: &Context) -> FieldResult<Option<i32>> {
+        Ok(self.load(context)?.time_bank)
+    }
+
+    /// The number of turns this player has run out of time on.
+    fn timeouts(&self, context: &Context) -> FieldResult<i32> {
+        Ok(self.load(context)?.timeouts)
+    }
+
+    /// When this player resigned, forfeited, was eliminated or was removed from the game.
+    fn departed_at(&self, context: &Context) -> FieldResult<Option<DateTime<Utc>>> {
+        Ok(self.load(context)?.departed_at)
+    }
+
     /// The game state that is specific to this player.
     fn state(&self, context: &Context) -> FieldResult<String> {
         Ok(self.load(context)?.state.to_string())
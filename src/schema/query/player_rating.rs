@@ -0,0 +1,94 @@
+use super::{Account, Context, Pagination, QueryWrapper, Universe};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use juniper::FieldResult;
+use uuid::Uuid;
+
+pub struct PlayerRating {
+    universe_id: Uuid,
+    account_id: Uuid,
+}
+
+impl QueryWrapper for PlayerRating {
+    type Model = data::PlayerRating;
+
+    fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        Universe::new(self.universe_id).load(context)?;
+        context
+            .player_ratings()
+            .load((self.universe_id, self.account_id))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Account {} has no rating in universe {}",
+                    self.account_id,
+                    self.universe_id
+                )
+            })
+    }
+}
+
+impl PlayerRating {
+    pub fn new(universe_id: Uuid, account_id: Uuid) -> Self {
+        Self {
+            universe_id,
+            account_id,
+        }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl PlayerRating {
+    /// The account which holds this rating.
+    fn account(&self, context: &Context) -> FieldResult<Account> {
+        Ok(Account::new(self.load(context)?.account_id))
+    }
+
+    /// The universe in which this rating was earned.
+    fn universe(&self, context: &Context) -> FieldResult<Universe> {
+        Ok(Universe::new(self.load(context)?.universe_id))
+    }
+
+    /// The Glicko-2 rating of the account. New players start at 1500.
+    fn rating(&self, context: &Context) -> FieldResult<f64> {
+        Ok(self.load(context)?.rating)
+    }
+
+    /// How uncertain the rating is. This shrinks as more games are played.
+    fn deviation(&self, context: &Context) -> FieldResult<f64> {
+        Ok(self.load(context)?.deviation)
+    }
+
+    /// How consistently the account performs, as the expected fluctuation of its rating.
+    fn volatility(&self, context: &Context) -> FieldResult<f64> {
+        Ok(self.load(context)?.volatility)
+    }
+
+    /// The number of finished games that contributed to this rating.
+    fn games_played(&self, context: &Context) -> FieldResult<i32> {
+        Ok(self.load(context)?.games_played)
+    }
+
+    /// When this rating last changed.
+    fn updated_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
+        Ok(self.load(context)?.updated_at)
+    }
+}
+
+#[juniper::graphql_object(Context = Context, name = "PlayerRatingPagination")]
+impl Pagination<PlayerRating> {
+    fn items(&self) -> &[PlayerRating] {
+        self.items()
+    }
+
+    fn total(&self) -> i32 {
+        self.total()
+    }
+
+    fn start(&self, context: &Context) -> juniper::FieldResult<Option<String>> {
+        self.start(context)
+    }
+
+    fn end(&self, context: &Context) -> juniper::FieldResult<Option<String>> {
+        self.end(context)
+    }
+}
@@ -0,0 +1,80 @@
+use super::{Archetype, Context, Map, Pagination, QueryWrapper, Universe};
+use data::{SearchKind, TryAsQuery};
+use diesel::prelude::*;
+
+#[derive(juniper::GraphQLUnion)]
+#[graphql(context = Context)]
+pub enum SearchHit {
+    Universe(Universe),
+    Archetype(Archetype),
+    Map(Map),
+}
+
+/// A single hit of a full-text search. Hits are not stored anywhere, so unlike the other
+/// wrappers this one holds the hit itself rather than the ID to load it by.
+pub struct SearchResult {
+    hit: data::SearchHit,
+}
+
+impl QueryWrapper for SearchResult {
+    type Model = data::SearchHit;
+
+    fn load(&self, _context: &Context) -> anyhow::Result<Self::Model> {
+        Ok(self.hit.clone())
+    }
+}
+
+impl SearchResult {
+    /// Searches universes, archetypes and maps for some text, best matches first. The `search`
+    /// should already be limited to what the searcher can see.
+    ///
+    /// The `search_document` columns that are searched, and the triggers that keep them up to
+    /// date, are created by the migrations in the data crate.
+    pub fn search(context: &Context, search: &data::TextSearch) -> anyhow::Result<Vec<Self>> {
+        let hits: Vec<data::SearchHit> =
+            context.transaction(|conn| Ok(search.try_as_query()?.load(conn)?))?;
+        Ok(hits.into_iter().map(|hit| Self { hit }).collect())
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl SearchResult {
+    /// The universe, archetype or map that matched the search.
+    fn hit(&self) -> SearchHit {
+        match self.hit.kind {
+            SearchKind::Universe => SearchHit::Universe(Universe::new(self.hit.id)),
+            SearchKind::Archetype => SearchHit::Archetype(Archetype::new(self.hit.id)),
+            SearchKind::Map => SearchHit::Map(Map::new(self.hit.id)),
+        }
+    }
+
+    /// How well the hit matched the search. Higher is better.
+    fn rank(&self) -> f64 {
+        self.hit.rank as f64
+    }
+
+    /// An excerpt of the matching text as HTML, with the matched words wrapped in `<b></b>` tags.
+    /// Any other markup in the text is escaped.
+    fn highlight(&self) -> &str {
+        &self.hit.highlight
+    }
+}
+
+#[juniper::graphql_object(Context = Context, name = "SearchResultPagination")]
+impl Pagination<SearchResult> {
+    fn items(&self) -> &[SearchResult] {
+        self.items()
+    }
+
+    fn total(&self) -> i32 {
+        self.total()
+    }
+
+    fn start(&self, context: &Context) -> juniper::FieldResult<Option<String>> {
+        self.start(context)
+    }
+
+    fn end(&self, context: &Context) -> juniper::FieldResult<Option<String>> {
+        self.end(context)
+    }
+}
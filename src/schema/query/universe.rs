@@ -1,9 +1,11 @@
 use super::{
-    Archetype, Context, Contributor, Map, OperationResult, Pagination, QueryWrapper,
+    Archetype, ChangeRequest, Context, Contributor, ContributorChange, Invite, Map,
+    OperationResult, Pagination, PlayerRating, QueryWrapper, UniverseDiff, UniverseStats,
     UniverseVersion,
 };
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
+use data::{ContributorRole, UniverseVisibility};
 use juniper::FieldResult;
 use uuid::Uuid;
 
@@ -18,14 +20,49 @@ impl QueryWrapper for Universe {
         context
             .universes()
             .load(self.id)
+            .filter(|universe| can_view(context, universe))
             .ok_or_else(|| anyhow!("Universe {} does not exist", self.id))
     }
 }
 
+/// Whether the authenticated account may see a universe. Private universes are only visible to
+/// the accounts involved in them.
+fn can_view(context: &Context, universe: &data::Universe) -> bool {
+    if universe.visibility != UniverseVisibility::Private {
+        return true;
+    }
+    context
+        .authenticated_account()
+        .and_then(|account_id| context.contributors().load((universe.id, account_id)))
+        .map(|contributor| contributor.role != ContributorRole::Declined)
+        .unwrap_or(false)
+}
+
 impl Universe {
     pub fn new(id: Uuid) -> Self {
         Self { id }
     }
+
+    /// Ensures that the authenticated account is a contributor to this universe.
+    pub fn assert_contributor(&self, context: &Context) -> anyhow::Result<()> {
+        let account_id = context.try_authenticated_account()?;
+        let is_contributor = context
+            .contributors()
+            .load((self.id, account_id))
+            .map(|contributor| {
+                contributor.role == ContributorRole::Owner
+                    || contributor.role == ContributorRole::Maintainer
+                    || contributor.role == ContributorRole::Contributor
+            })
+            .unwrap_or(false);
+        anyhow::ensure!(
+            is_contributor,
+            "You ({}) are not a contributor to this universe ({})",
+            account_id,
+            self.id,
+        );
+        Ok(())
+    }
 }
 
 #[juniper::graphql_object(Context = Context)]
@@ -40,6 +77,11 @@ impl Universe {
         Ok(self.load(context)?.name.to_string())
     }
 
+    /// Who is able to find and see this universe.
+    fn visibility(&self, context: &Context) -> FieldResult<UniverseVisibility> {
+        Ok(self.load(context)?.visibility)
+    }
+
     /// When this universe was created.
     fn created_at(&self, context: &Context) -> FieldResult<DateTime<Utc>> {
         Ok(self.load(context)?.created_at)
@@ -51,7 +93,9 @@ impl Universe {
         context: &Context,
         search: Option<data::ContributorSearch>,
     ) -> FieldResult<Pagination<Contributor>> {
-        let search = search.unwrap_or_default().for_universe(self.id);
+        let search = search
+            .unwrap_or_default()
+            .for_universe(self.load(context)?.id);
         let items = context
             .contributors()
             .search(&search)?
@@ -60,22 +104,85 @@ impl Universe {
         Ok(Pagination::new(search, items))
     }
 
-    /// Archetypes which belong to this universe.
+    /// The ratings of the accounts who have finished games in this universe, highest first.
+    fn leaderboard(
+        &self,
+        context: &Context,
+        search: Option<data::PlayerRatingSearch>,
+    ) -> FieldResult<Pagination<PlayerRating>> {
+        let search = search
+            .unwrap_or_default()
+            .for_universe(self.load(context)?.id);
+        let items = context
+            .player_ratings()
+            .search(&search)?
+            .into_iter()
+            .map(|rating| PlayerRating::new(rating.universe_id, rating.account_id));
+        Ok(Pagination::new(search, items))
+    }
+
+    /// The invites you have issued for this universe.
+    fn invites(&self, context: &Context) -> FieldResult<Vec<Invite>> {
+        let mut invites: Vec<_> = context
+            .invites()
+            .for_universe(&self.load(context)?.id)
+            .into_iter()
+            .filter(|invite| Some(invite.created_by) == context.authenticated_account())
+            .collect();
+        invites.sort_by_key(|invite| std::cmp::Reverse(invite.created_at));
+        Ok(invites
+            .into_iter()
+            .map(|invite| Invite::new(invite.id))
+            .collect())
+    }
+
+    /// Every change made to the roles of this universe's contributors, oldest first.
+    fn contributor_history(&self, context: &Context) -> FieldResult<Vec<ContributorChange>> {
+        let mut changes = context
+            .contributor_changes()
+            .for_universe(&self.load(context)?.id);
+        changes.sort_by_key(|change| change.created_at);
+        Ok(changes
+            .into_iter()
+            .map(|change| ContributorChange::new(change.id))
+            .collect())
+    }
+
+    /// Change requests proposed against this universe, newest first. Only open change requests
+    /// are included unless all is set.
+    #[graphql(arguments(all(default = false)))]
+    fn change_requests(&self, context: &Context, all: bool) -> FieldResult<Vec<ChangeRequest>> {
+        let mut change_requests: Vec<_> = context
+            .change_requests()
+            .for_universe(&self.load(context)?.id)
+            .into_iter()
+            .filter(|change_request| all || change_request.state == data::ChangeRequestState::Open)
+            .collect();
+        change_requests.sort_by_key(|change_request| std::cmp::Reverse(change_request.created_at));
+        Ok(change_requests
+            .into_iter()
+            .map(|change_request| ChangeRequest::new(change_request.id))
+            .collect())
+    }
+
+    /// Archetypes which belong to this universe. Deleted archetypes are not included.
     fn archetypes(&self, context: &Context) -> FieldResult<Vec<Archetype>> {
         Ok(context
             .archetypes()
-            .for_universe(&self.id)
+            .for_universe(&self.load(context)?.id)
             .into_iter()
+            .filter(|archetype| archetype.deleted_at.is_none())
             .map(|archetype| Archetype::new(archetype.id))
             .collect())
     }
 
-    /// Maps which belong to this universe.
+    /// Maps which belong to this universe. Deleted maps are not included.
     fn maps(&self, context: &Context) -> FieldResult<Vec<Map>> {
         Ok(context
             .maps()
             .for_universe(&self.load(context)?.id)
             .into_iter()
+            .filter(|map| map.deleted_at.is_none())
             .map(|map| Map::new(map.id))
             .collect())
     }
@@ -98,6 +205,54 @@ impl Universe {
             .load_current(self.load(context)?.id, unreleased)?
             .map(|version| version.version))
     }
+
+    /// The released versions of this universe, most recent first.
+    fn changelog(&self, context: &Context) -> FieldResult<Vec<UniverseVersion>> {
+        let mut releases = context
+            .universe_versions()
+            .for_universe(&self.load(context)?.id)
+            .into_iter()
+            .filter(|version| version.released_at.is_some())
+            .collect::<Vec<_>>();
+        releases.sort_by_key(|version| std::cmp::Reverse(version.version));
+        Ok(releases
+            .into_iter()
+            .map(|version| UniverseVersion::new(version.universe_id, version.version))
+            .collect())
+    }
+
+    /// How popular this universe is.
+    fn stats(&self, context: &Context) -> FieldResult<UniverseStats> {
+        Ok(UniverseStats::new(self.load(context)?.id))
+    }
+
+    /// Whether you have favorited this universe.
+    fn favorited(&self, context: &Context) -> FieldResult<bool> {
+        let universe = self.load(context)?;
+        Ok(context
+            .authenticated_account()
+            .and_then(|account_id| context.universe_favorites().load((universe.id, account_id)))
+            .is_some())
+    }
+
+    /// The rating you have given this universe, if any.
+    fn my_rating(&self, context: &Context) -> FieldResult<Option<i32>> {
+        let universe = self.load(context)?;
+        Ok(context
+            .authenticated_account()
+            .and_then(|account_id| context.universe_ratings().load((universe.id, account_id)))
+            .map(|rating| rating.rating))
+    }
+
+    /// The archetypes and maps which changed between two versions of this universe.
+    fn diff(&self, context: &Context, from: i32, to: i32) -> FieldResult<UniverseDiff> {
+        Ok(UniverseDiff::new(
+            context,
+            self.load(context)?.id,
+            from,
+            to,
+        )?)
+    }
 }
 
 #[juniper::graphql_object(Context = Context, name = "UniversePagination")]
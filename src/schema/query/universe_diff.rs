@@ -0,0 +1,300 @@
+use super::{Archetype, ArchetypeVersion, Context, Map, MapVersion, QueryWrapper, UniverseVersion};
+use juniper::FieldResult;
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// The way in which an archetype or map differs between two versions of a universe.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, juniper::GraphQLEnum)]
+pub enum VersionChange {
+    /// Only included in the newer version.
+    Added,
+    /// Only included in the older version.
+    Removed,
+    /// Included in both versions, but pinned to different versions.
+    Upgraded,
+}
+
+impl VersionChange {
+    fn between(from: Option<i32>, to: Option<i32>) -> Self {
+        match (from, to) {
+            (None, _) => Self::Added,
+            (_, None) => Self::Removed,
+            _ => Self::Upgraded,
+        }
+    }
+}
+
+/// Pairs up the versions pinned in two universe versions, keeping only those that differ.
+fn changed_pins(
+    from: impl IntoIterator<Item = (Uuid, i32)>,
+    to: impl IntoIterator<Item = (Uuid, i32)>,
+) -> Vec<(Uuid, Option<i32>, Option<i32>)> {
+    let mut pins: BTreeMap<Uuid, (Option<i32>, Option<i32>)> = BTreeMap::new();
+    for (id, version) in from {
+        pins.entry(id).or_default().0 = Some(version);
+    }
+    for (id, version) in to {
+        pins.entry(id).or_default().1 = Some(version);
+    }
+    pins.into_iter()
+        .filter(|(_, (from, to))| from != to)
+        .map(|(id, (from, to))| (id, from, to))
+        .collect()
+}
+
+fn summarize(kind: &str, name: &str, from: Option<i32>, to: Option<i32>) -> String {
+    match (from, to) {
+        (None, Some(to)) => format!("Added {} {} (version {})", kind, name, to),
+        (Some(from), None) => format!("Removed {} {} (version {})", kind, name, from),
+        (Some(from), Some(to)) => {
+            format!("Upgraded {} {} from version {} to {}", kind, name, from, to)
+        }
+        (None, None) => unreachable!("unchanged pins are not included in a diff"),
+    }
+}
+
+pub(super) fn script_diff(
+    name: &str,
+    from: Option<(i32, String)>,
+    to: Option<(i32, String)>,
+) -> String {
+    let label = |version: &Option<(i32, String)>| match version {
+        Some((version, _)) => format!("{}@{}", name, version),
+        None => String::from("/dev/null"),
+    };
+    let from_label = label(&from);
+    let to_label = label(&to);
+    let from_script = from.map(|(_, script)| script).unwrap_or_default();
+    let to_script = to.map(|(_, script)| script).unwrap_or_default();
+    TextDiff::from_lines(&from_script, &to_script)
+        .unified_diff()
+        .header(&from_label, &to_label)
+        .to_string()
+}
+
+pub struct UniverseDiff {
+    universe_id: Uuid,
+    from: Option<i32>,
+    to: i32,
+    archetypes: Vec<ArchetypeDiff>,
+    maps: Vec<MapDiff>,
+}
+
+impl UniverseDiff {
+    pub fn new(context: &Context, universe_id: Uuid, from: i32, to: i32) -> anyhow::Result<Self> {
+        UniverseVersion::new(universe_id, from).load(context)?;
+        Self::between(context, universe_id, Some(from), to)
+    }
+
+    /// Compares a version of a universe against an empty universe, as if it were the first release.
+    pub fn initial(context: &Context, universe_id: Uuid, to: i32) -> anyhow::Result<Self> {
+        Self::between(context, universe_id, None, to)
+    }
+
+    fn between(
+        context: &Context,
+        universe_id: Uuid,
+        from: Option<i32>,
+        to: i32,
+    ) -> anyhow::Result<Self> {
+        UniverseVersion::new(universe_id, to).load(context)?;
+
+        let archetype_pins = |version: Option<i32>| {
+            version
+                .map(|version| {
+                    context
+                        .universe_version_archetypes()
+                        .for_universe_version(&universe_id, &version)
+                })
+                .unwrap_or_default()
+                .into_iter()
+                .map(|pin| (pin.archetype_id, pin.archetype_version))
+        };
+        let archetypes = changed_pins(archetype_pins(from), archetype_pins(Some(to)))
+            .into_iter()
+            .map(|(archetype_id, from, to)| ArchetypeDiff {
+                archetype_id,
+                from,
+                to,
+            })
+            .collect();
+
+        let map_pins = |version: Option<i32>| {
+            version
+                .map(|version| {
+                    context
+                        .universe_version_maps()
+                        .for_universe_version(&universe_id, &version)
+                })
+                .unwrap_or_default()
+                .into_iter()
+                .map(|pin| (pin.map_id, pin.map_version))
+        };
+        let maps = changed_pins(map_pins(from), map_pins(Some(to)))
+            .into_iter()
+            .map(|(map_id, from, to)| MapDiff { map_id, from, to })
+            .collect();
+
+        Ok(Self {
+            universe_id,
+            from,
+            to,
+            archetypes,
+            maps,
+        })
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl UniverseDiff {
+    /// The older version being compared. If null, the newer version is being compared against
+    /// an empty universe.
+    fn from(&self) -> Option<UniverseVersion> {
+        self.from
+            .map(|version| UniverseVersion::new(self.universe_id, version))
+    }
+
+    /// The newer version being compared.
+    fn to(&self) -> UniverseVersion {
+        UniverseVersion::new(self.universe_id, self.to)
+    }
+
+    /// Archetypes which were added, removed or upgraded between the two versions.
+    fn archetypes(&self) -> &[ArchetypeDiff] {
+        self.archetypes.as_slice()
+    }
+
+    /// Maps which were added, removed or upgraded between the two versions.
+    fn maps(&self) -> &[MapDiff] {
+        self.maps.as_slice()
+    }
+
+    /// A human readable line describing each change, suitable for display to players.
+    fn summary(&self, context: &Context) -> FieldResult<Vec<String>> {
+        let archetypes = self
+            .archetypes
+            .iter()
+            .map(|diff| -> anyhow::Result<String> {
+                let name = Archetype::new(diff.archetype_id).load(context)?.name;
+                Ok(summarize("archetype", &name, diff.from, diff.to))
+            });
+        let maps = self.maps.iter().map(|diff| -> anyhow::Result<String> {
+            let name = Map::new(diff.map_id).load(context)?.name;
+            Ok(summarize("map", &name, diff.from, diff.to))
+        });
+        Ok(archetypes.chain(maps).collect::<anyhow::Result<_>>()?)
+    }
+}
+
+pub struct ArchetypeDiff {
+    archetype_id: Uuid,
+    from: Option<i32>,
+    to: Option<i32>,
+}
+
+impl ArchetypeDiff {
+    fn load_script(
+        &self,
+        context: &Context,
+        version: Option<i32>,
+    ) -> anyhow::Result<Option<(i32, String)>> {
+        version
+            .map(|version| {
+                let archetype_version =
+                    ArchetypeVersion::new(self.archetype_id, version).load(context)?;
+                Ok((version, archetype_version.script))
+            })
+            .transpose()
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl ArchetypeDiff {
+    /// The archetype that changed.
+    fn archetype(&self) -> Archetype {
+        Archetype::new(self.archetype_id)
+    }
+
+    /// How the archetype changed.
+    fn change(&self) -> VersionChange {
+        VersionChange::between(self.from, self.to)
+    }
+
+    /// The version of the archetype in the older universe version, if it was included.
+    fn from(&self) -> Option<ArchetypeVersion> {
+        self.from
+            .map(|version| ArchetypeVersion::new(self.archetype_id, version))
+    }
+
+    /// The version of the archetype in the newer universe version, if it is included.
+    fn to(&self) -> Option<ArchetypeVersion> {
+        self.to
+            .map(|version| ArchetypeVersion::new(self.archetype_id, version))
+    }
+
+    /// A unified diff of the archetype's script between the two versions.
+    fn script_diff(&self, context: &Context) -> FieldResult<String> {
+        let name = Archetype::new(self.archetype_id).load(context)?.name;
+        Ok(script_diff(
+            &name,
+            self.load_script(context, self.from)?,
+            self.load_script(context, self.to)?,
+        ))
+    }
+}
+
+pub struct MapDiff {
+    map_id: Uuid,
+    from: Option<i32>,
+    to: Option<i32>,
+}
+
+impl MapDiff {
+    fn load_script(
+        &self,
+        context: &Context,
+        version: Option<i32>,
+    ) -> anyhow::Result<Option<(i32, String)>> {
+        version
+            .map(|version| {
+                let map_version = MapVersion::new(self.map_id, version).load(context)?;
+                Ok((version, map_version.script))
+            })
+            .transpose()
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl MapDiff {
+    /// The map that changed.
+    fn map(&self) -> Map {
+        Map::new(self.map_id)
+    }
+
+    /// How the map changed.
+    fn change(&self) -> VersionChange {
+        VersionChange::between(self.from, self.to)
+    }
+
+    /// The version of the map in the older universe version, if it was included.
+    fn from(&self) -> Option<MapVersion> {
+        self.from
+            .map(|version| MapVersion::new(self.map_id, version))
+    }
+
+    /// The version of the map in the newer universe version, if it is included.
+    fn to(&self) -> Option<MapVersion> {
+        self.to.map(|version| MapVersion::new(self.map_id, version))
+    }
+
+    /// A unified diff of the map's script between the two versions.
+    fn script_diff(&self, context: &Context) -> FieldResult<String> {
+        let name = Map::new(self.map_id).load(context)?.name;
+        Ok(script_diff(
+            &name,
+            self.load_script(context, self.from)?,
+            self.load_script(context, self.to)?,
+        ))
+    }
+}
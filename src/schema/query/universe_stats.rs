@@ -0,0 +1,61 @@
+use super::Context;
+use anyhow::anyhow;
+use juniper::FieldResult;
+use uuid::Uuid;
+
+/// How popular a universe is.
+pub struct UniverseStats {
+    universe_id: Uuid,
+}
+
+impl UniverseStats {
+    pub fn new(universe_id: Uuid) -> Self {
+        Self { universe_id }
+    }
+
+    fn load(&self, context: &Context) -> anyhow::Result<crate::schema::loader::UniverseCounts> {
+        context
+            .universe_stats()
+            .load(self.universe_id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Statistics for universe {} could not be loaded",
+                    self.universe_id
+                )
+            })
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl UniverseStats {
+    /// The number of games that have been finished in any version of this universe.
+    fn games_played(&self, context: &Context) -> FieldResult<i32> {
+        Ok(self.load(context)?.games_played as i32)
+    }
+
+    /// The number of games in this universe which have not yet ended.
+    fn active_games(&self, context: &Context) -> FieldResult<i32> {
+        Ok(self.load(context)?.active_games as i32)
+    }
+
+    /// The number of different accounts that have played a game in this universe.
+    fn unique_players(&self, context: &Context) -> FieldResult<i32> {
+        Ok(self.load(context)?.unique_players as i32)
+    }
+
+    /// The number of accounts that have favorited this universe.
+    fn favorites(&self, context: &Context) -> FieldResult<i32> {
+        Ok(self.load(context)?.favorites as i32)
+    }
+
+    /// The number of accounts that have rated this universe.
+    fn ratings(&self, context: &Context) -> FieldResult<i32> {
+        Ok(self.load(context)?.ratings as i32)
+    }
+
+    /// The average rating (from 1 to 5) of this universe, if anyone has rated it.
+    fn average_rating(&self, context: &Context) -> FieldResult<Option<f64>> {
+        let counts = self.load(context)?;
+        Ok(Some(counts.rating_total as f64 / counts.ratings as f64).filter(|_| counts.ratings > 0))
+    }
+}
@@ -0,0 +1,57 @@
+use super::{ArchetypeVersion, Context, MapVersion, UniverseVersion};
+use crate::schema::validation::Problem;
+use uuid::Uuid;
+
+pub struct UniverseValidation {
+    universe_id: Uuid,
+    version: i32,
+    problems: Vec<Problem>,
+}
+
+impl UniverseValidation {
+    pub fn new(universe_id: Uuid, version: i32, problems: Vec<Problem>) -> Self {
+        Self {
+            universe_id,
+            version,
+            problems,
+        }
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl UniverseValidation {
+    /// The version of the universe that was validated.
+    fn universe(&self) -> UniverseVersion {
+        UniverseVersion::new(self.universe_id, self.version)
+    }
+
+    /// Whether this version is ready to be published.
+    fn valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// The problems which must be fixed before this version can be published.
+    fn problems(&self) -> &[Problem] {
+        self.problems.as_slice()
+    }
+}
+
+#[juniper::graphql_object(Context = Context, name = "ValidationProblem")]
+impl Problem {
+    /// A description of the problem.
+    fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The archetype version with the problem, if the problem is with an archetype.
+    fn archetype(&self) -> Option<ArchetypeVersion> {
+        self.archetype
+            .map(|(archetype_id, version)| ArchetypeVersion::new(archetype_id, version))
+    }
+
+    /// The map version with the problem, if the problem is with a map.
+    fn map(&self) -> Option<MapVersion> {
+        self.map
+            .map(|(map_id, version)| MapVersion::new(map_id, version))
+    }
+}
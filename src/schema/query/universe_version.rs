@@ -1,4 +1,6 @@
-use super::{ArchetypeVersion, Context, MapVersion, OperationResult, QueryWrapper};
+use super::{
+    ArchetypeVersion, Context, MapVersion, OperationResult, QueryWrapper, Universe, UniverseDiff,
+};
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
 use juniper::FieldResult;
@@ -13,6 +15,7 @@ impl QueryWrapper for UniverseVersion {
     type Model = data::UniverseVersion;
 
     fn load(&self, context: &Context) -> anyhow::Result<Self::Model> {
+        self.load_universe(context)?;
         context
             .universe_versions()
             .load((self.universe_id, self.version))
@@ -35,10 +38,7 @@ impl UniverseVersion {
     }
 
     fn load_universe(&self, context: &Context) -> anyhow::Result<data::Universe> {
-        context
-            .universes()
-            .load(self.universe_id)
-            .ok_or_else(|| anyhow!("Universe {} does not exist", self.universe_id))
+        Universe::new(self.universe_id).load(context)
     }
 }
 
@@ -69,6 +69,66 @@ impl UniverseVersion {
         Ok(self.load(context)?.released_at)
     }
 
+    /// The number of games that have been finished with this version of the universe.
+    fn games_played(&self, context: &Context) -> FieldResult<i32> {
+        let version = self.load(context)?;
+        let counts = context
+            .universe_version_stats()
+            .load((version.universe_id, version.version))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Statistics for universe {} version {} could not be loaded",
+                    version.universe_id,
+                    version.version
+                )
+            })?;
+        Ok(counts.games_played as i32)
+    }
+
+    /// When this version was yanked. Yanked versions are not used for new games.
+    fn yanked_at(&self, context: &Context) -> FieldResult<Option<DateTime<Utc>>> {
+        Ok(self.load(context)?.yanked_at)
+    }
+
+    /// When this version was deprecated. Deprecated versions can still be played, but should be
+    /// avoided.
+    fn deprecated_at(&self, context: &Context) -> FieldResult<Option<DateTime<Utc>>> {
+        Ok(self.load(context)?.deprecated_at)
+    }
+
+    /// Notes written by the publisher when this version was released.
+    fn release_notes(&self, context: &Context) -> FieldResult<Option<String>> {
+        Ok(self.load(context)?.release_notes)
+    }
+
+    /// The released version that came before this one, if any.
+    fn previous_release(&self, context: &Context) -> FieldResult<Option<UniverseVersion>> {
+        let universe = self.load(context)?;
+        Ok(context
+            .universe_versions()
+            .load_previous_release(universe.universe_id, universe.version)
+            .map(|version| UniverseVersion::new(version.universe_id, version.version)))
+    }
+
+    /// The archetypes and maps which have changed since the previous release. For the first
+    /// release, everything is considered to have been added.
+    fn changes(&self, context: &Context) -> FieldResult<UniverseDiff> {
+        let universe = self.load(context)?;
+        let previous = context
+            .universe_versions()
+            .load_previous_release(universe.universe_id, universe.version);
+        let diff = match previous {
+            Some(previous) => UniverseDiff::new(
+                context,
+                universe.universe_id,
+                previous.version,
+                universe.version,
+            )?,
+            None => UniverseDiff::initial(context, universe.universe_id, universe.version)?,
+        };
+        Ok(diff)
+    }
+
     /// Archetypes available in this version.
     fn archetypes(&self, context: &Context) -> FieldResult<Vec<ArchetypeVersion>> {
         let universe = self.load(context)?;
@@ -0,0 +1,7 @@
+use sha2::{Digest, Sha256};
+
+/// Identifies the contents of a script. A client passes back the revision it last saw when
+/// editing a script, so that edits made in the meantime are not silently overwritten.
+pub fn of(script: &str) -> String {
+    format!("{:x}", Sha256::digest(script.as_bytes()))
+}
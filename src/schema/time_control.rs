@@ -0,0 +1,29 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// When the current turn of a game must end, if the game has a time control. A player may use
+/// the per-turn limit, and once that runs out, whatever remains in their time bank.
+pub fn turn_deadline(game: &data::Game, player: &data::Player) -> Option<DateTime<Utc>> {
+    if game.turn_limit.is_none() && game.time_bank.is_none() {
+        return None;
+    }
+    let started_at = game.turn_started_at?;
+    let allowed = game.turn_limit.unwrap_or(0) + player.time_bank.unwrap_or(0);
+    Some(started_at + Duration::seconds(allowed as i64))
+}
+
+/// The time a player has left in their bank after ending a turn at the given time. Time spent
+/// beyond the per-turn limit is taken from the bank.
+pub fn bank_after_turn(
+    game: &data::Game,
+    player: &data::Player,
+    ended_at: DateTime<Utc>,
+) -> Option<i32> {
+    let bank = player.time_bank?;
+    let started_at = match game.turn_started_at {
+        Some(started_at) => started_at,
+        None => return Some(bank),
+    };
+    let elapsed = (ended_at - started_at).num_seconds() as i32;
+    let overtime = (elapsed - game.turn_limit.unwrap_or(0)).max(0);
+    Some((bank - overtime).max(0))
+}
@@ -0,0 +1,184 @@
+use crate::engine;
+use data::*;
+use diesel::prelude::*;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Something that prevents a version of a universe from being released.
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub message: String,
+    pub archetype: Option<(Uuid, i32)>,
+    pub map: Option<(Uuid, i32)>,
+}
+
+impl Problem {
+    fn universe(message: String) -> Self {
+        Self {
+            message,
+            archetype: None,
+            map: None,
+        }
+    }
+
+    fn archetype(archetype: &ArchetypeVersion, message: String) -> Self {
+        Self {
+            message,
+            archetype: Some((archetype.archetype_id, archetype.version)),
+            map: None,
+        }
+    }
+
+    fn map(map: &MapVersion, message: String) -> Self {
+        Self {
+            message,
+            archetype: None,
+            map: Some((map.map_id, map.version)),
+        }
+    }
+}
+
+/// The outcome of validating a version of a universe.
+pub struct Validation {
+    pub problems: Vec<Problem>,
+    /// The number of players each map version is designed for, if it declares one. These are
+    /// recorded when the version is released, so that they need not be worked out again.
+    pub player_counts: Vec<(Uuid, i32, Option<i32>)>,
+}
+
+/// The scripts pinned in a version of a universe. These are loaded up front, so that the engine
+/// does not run while a transaction is open.
+pub struct UniverseVersionScripts {
+    archetypes: Vec<(Archetype, ArchetypeVersion)>,
+    maps: Vec<(Map, MapVersion)>,
+}
+
+impl UniverseVersionScripts {
+    pub fn load(universe_id: Uuid, version: i32, conn: &DbConnection) -> anyhow::Result<Self> {
+        let archetype_pins: Vec<UniverseVersionArchetype> = universe_version_archetypes::table
+            .filter(universe_version_archetypes::universe_id.eq(universe_id))
+            .filter(universe_version_archetypes::universe_version.eq(version))
+            .order_by(universe_version_archetypes::archetype_id.asc())
+            .load(conn)?;
+        let mut archetypes = vec![];
+        for pin in archetype_pins {
+            let archetype: Archetype = archetypes::table.find(pin.archetype_id).get_result(conn)?;
+            let archetype_version: ArchetypeVersion = archetype_versions::table
+                .filter(archetype_versions::archetype_id.eq(pin.archetype_id))
+                .filter(archetype_versions::version.eq(pin.archetype_version))
+                .get_result(conn)?;
+            archetypes.push((archetype, archetype_version));
+        }
+
+        let map_pins: Vec<UniverseVersionMap> = universe_version_maps::table
+            .filter(universe_version_maps::universe_id.eq(universe_id))
+            .filter(universe_version_maps::universe_version.eq(version))
+            .order_by(universe_version_maps::map_id.asc())
+            .load(conn)?;
+        let mut maps = vec![];
+        for pin in map_pins {
+            let map: Map = maps::table.find(pin.map_id).get_result(conn)?;
+            let map_version: MapVersion = map_versions::table
+                .filter(map_versions::map_id.eq(pin.map_id))
+                .filter(map_versions::version.eq(pin.map_version))
+                .get_result(conn)?;
+            maps.push((map, map_version));
+        }
+
+        Ok(Self { archetypes, maps })
+    }
+
+    /// Whether these are the same scripts as another set, so that a validation that was done
+    /// outside of a transaction can be trusted within it.
+    pub fn same_as(&self, other: &Self) -> bool {
+        let archetypes = self
+            .archetypes
+            .iter()
+            .map(|(_, version)| (version.archetype_id, version.version, &version.script));
+        let other_archetypes = other
+            .archetypes
+            .iter()
+            .map(|(_, version)| (version.archetype_id, version.version, &version.script));
+        let maps = self
+            .maps
+            .iter()
+            .map(|(_, version)| (version.map_id, version.version, &version.script));
+        let other_maps = other
+            .maps
+            .iter()
+            .map(|(_, version)| (version.map_id, version.version, &version.script));
+        archetypes.eq(other_archetypes) && maps.eq(other_maps)
+    }
+
+    /// Checks that this version of a universe is complete enough to be released, returning every
+    /// problem that was found and the number of players each map is designed for.
+    pub fn validate(&self) -> Validation {
+        let mut problems = vec![];
+        let mut player_counts = vec![];
+
+        let mut archetype_names = HashSet::new();
+        for (archetype, archetype_version) in &self.archetypes {
+            if archetype_version.script.trim().is_empty() {
+                problems.push(Problem::archetype(
+                    archetype_version,
+                    format!("Archetype {} has an empty script", archetype.name),
+                ));
+            } else if let Err(error) = engine::check(&archetype_version.script) {
+                problems.push(Problem::archetype(
+                    archetype_version,
+                    format!("Archetype {} failed to load: {}", archetype.name, error),
+                ));
+            }
+            archetype_names.insert(archetype.name.clone());
+        }
+
+        if self.maps.is_empty() {
+            problems.push(Problem::universe(String::from(
+                "At least one map must be included",
+            )));
+        }
+        for (map, map_version) in &self.maps {
+            if map_version.script.trim().is_empty() {
+                problems.push(Problem::map(
+                    map_version,
+                    format!("Map {} has an empty script", map.name),
+                ));
+                continue;
+            }
+            match engine::referenced_archetypes(&map_version.script) {
+                Ok(references) => {
+                    for name in references {
+                        if !archetype_names.contains(&name) {
+                            problems.push(Problem::map(
+                                map_version,
+                                format!(
+                                    "Map {} references archetype {}, which is not included",
+                                    map.name, name
+                                ),
+                            ));
+                        }
+                    }
+                }
+                Err(error) => {
+                    problems.push(Problem::map(
+                        map_version,
+                        format!("Map {} failed to load: {}", map.name, error),
+                    ));
+                    continue;
+                }
+            }
+            match engine::player_count(&map_version.script) {
+                Ok(count) => player_counts.push((map_version.map_id, map_version.version, count)),
+                Err(error) => problems.push(Problem::map(
+                    map_version,
+                    format!("Map {} has an invalid player count: {}", map.name, error),
+                )),
+            }
+        }
+
+        Validation {
+            problems,
+            player_counts,
+        }
+    }
+}